@@ -12,6 +12,7 @@ struct UnsignedTmpl<'a> {
     id: &'a str,
     default_value: &'a str,
     has_default: bool,
+    required: bool,
 }
 
 #[derive(Template)]
@@ -22,6 +23,7 @@ struct SignedTmpl<'a> {
     id: &'a str,
     default_value: &'a str,
     has_default: bool,
+    required: bool,
 }
 
 #[derive(Template)]
@@ -32,6 +34,7 @@ struct FloatTmpl<'a> {
     id: &'a str,
     default_value: &'a str,
     has_default: bool,
+    required: bool,
 }
 
 #[derive(Template)]
@@ -133,6 +136,8 @@ fn main() {
     let xml = roxmltree::Document::parse(&content).unwrap();
 
     let mut output = String::new();
+    // (kind, name) of every generated type, to generate round-trip tests
+    let mut generated: Vec<(&str, String)> = vec![];
 
     for element in xml
         .descendants()
@@ -144,6 +149,8 @@ fn main() {
         let default_value = element.attribute("default");
         let has_default = default_value.is_some();
         let default_value = default_value.unwrap_or("0");
+        // required elements without a default value are expected to have a non-empty body
+        let required = !has_default && element.attribute("minOccurs").is_some_and(|m| m != "0");
 
         let doc = element
             .children()
@@ -154,13 +161,16 @@ fn main() {
 
         let name = adjust_name(raw_name);
 
-        let rendered = match element.attribute("type").unwrap() {
+        let kind = element.attribute("type").unwrap();
+        generated.push((kind, name.to_string()));
+        let rendered = match kind {
             "uinteger" => UnsignedTmpl {
                 doc: &doc,
                 name,
                 id,
                 default_value,
                 has_default,
+                required,
             }
             .render()
             .unwrap(),
@@ -170,6 +180,7 @@ fn main() {
                 id,
                 default_value,
                 has_default,
+                required,
             }
             .render()
             .unwrap(),
@@ -179,6 +190,7 @@ fn main() {
                 id,
                 default_value,
                 has_default,
+                required,
             }
             .render()
             .unwrap(),
@@ -248,6 +260,12 @@ fn main() {
     ];
 
     for &(name, id, default_value, doc) in extra_elements {
+        let kind = if name == "DocType" {
+            "string"
+        } else {
+            "uinteger"
+        };
+        generated.push((kind, name.to_string()));
         let rendered = match name {
             "DocType" => TextTmpl {
                 doc,
@@ -264,6 +282,7 @@ fn main() {
                 id,
                 default_value,
                 has_default: true,
+                required: false,
             }
             .render()
             .unwrap(),
@@ -273,4 +292,36 @@ fn main() {
     }
 
     fs::write(&dest_path, output).unwrap();
+
+    fs::write(
+        Path::new(&out_dir).join("generated_tests.rs"),
+        generate_round_trip_tests(&generated),
+    )
+    .unwrap();
+}
+
+/// Generate a test per element type, round-tripping sample values of every generated type.
+///
+/// The samples and the `round_trip` helper are defined in the tests of `leaf.rs`.
+fn generate_round_trip_tests(generated: &[(&str, String)]) -> String {
+    let mut output = String::new();
+    for (kind, samples) in [
+        ("uinteger", "unsigned_samples"),
+        ("integer", "signed_samples"),
+        ("float", "float_samples"),
+        ("string", "text_samples"),
+        ("utf-8", "text_samples"),
+        ("binary", "bin_samples"),
+        ("date", "date_samples"),
+    ] {
+        output.push_str(&format!(
+            "#[test]\nfn round_trip_generated_{}() {{\n",
+            kind.replace('-', "_")
+        ));
+        for (_, name) in generated.iter().filter(|(k, _)| *k == kind) {
+            output.push_str(&format!("    round_trip({name}, {samples}());\n"));
+        }
+        output.push_str("}\n");
+    }
+    output
 }
@@ -0,0 +1,98 @@
+//! Helpers for working with attachments.
+
+use std::hash::{BuildHasher, Hasher};
+
+use crate::leaf::{FileData, FileMediaType, FileName, FileUid};
+use crate::master::AttachedFile;
+
+/// Media types of common attachments, by lowercase file extension.
+const MEDIA_TYPES: &[(&str, &str)] = &[
+    ("ttf", "application/x-truetype-font"),
+    ("otf", "application/vnd.ms-opentype"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("png", "image/png"),
+    ("webp", "image/webp"),
+    ("gif", "image/gif"),
+    ("txt", "text/plain"),
+    ("xml", "application/xml"),
+    ("json", "application/json"),
+];
+
+/// The media type of a file from its extension, `application/octet-stream` when unknown.
+fn media_type(name: &str) -> &'static str {
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    MEDIA_TYPES
+        .iter()
+        .find(|(ext, _)| extension.as_deref() == Some(*ext))
+        .map_or("application/octet-stream", |(_, media_type)| media_type)
+}
+
+/// A random non-zero UID, from the randomly seeded hasher of the standard library.
+fn random_uid() -> u64 {
+    loop {
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        if let Ok(now) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+            hasher.write_u128(now.as_nanos());
+        }
+        let uid = hasher.finish();
+        if uid != 0 {
+            return uid;
+        }
+    }
+}
+
+impl AttachedFile {
+    /// Create an attachment with a random `FileUID`, and the `FileMediaType` inferred from the
+    /// extension of `name`, e.g. `application/x-truetype-font` for a `.ttf` font.
+    ///
+    /// Unknown extensions get `application/octet-stream`, see [`AttachedFile::with_media_type`]
+    /// to set the media type explicitly.
+    pub fn new(name: &str, data: Vec<u8>) -> Self {
+        AttachedFile {
+            file_name: FileName(name.to_string()),
+            file_media_type: FileMediaType(media_type(name).to_string()),
+            file_data: FileData(data.into()),
+            file_uid: FileUid(random_uid()),
+            ..Default::default()
+        }
+    }
+
+    /// Replace the media type of the attachment.
+    pub fn with_media_type(mut self, media_type: &str) -> Self {
+        self.file_media_type = FileMediaType(media_type.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::master::AttachedFile;
+
+    #[test]
+    fn test_new() {
+        let font = AttachedFile::new("Arial.TTF", vec![0, 1, 0, 0]);
+        assert_eq!(&*font.file_media_type, "application/x-truetype-font");
+        assert_eq!(&*font.file_name, "Arial.TTF");
+        assert_eq!(&font.file_data[..], [0, 1, 0, 0]);
+        assert_ne!(*font.file_uid, 0);
+        assert_ne!(
+            *font.file_uid,
+            *AttachedFile::new("Arial.ttf", vec![]).file_uid
+        );
+
+        let cover = AttachedFile::new("cover.jpg", vec![]);
+        assert_eq!(&*cover.file_media_type, "image/jpeg");
+
+        let subtitles = AttachedFile::new("notes", vec![]).with_media_type("text/x-ssa");
+        assert_eq!(&*subtitles.file_media_type, "text/x-ssa");
+        assert_eq!(
+            &*AttachedFile::new("notes", vec![]).file_media_type,
+            "application/octet-stream"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Helpers for working with audio settings.
+
+use crate::leaf::Emphasis;
+use crate::master::Audio;
+use crate::video::kind_enum;
+
+kind_enum! {
+    /// Emphasis applied on audio samples, from `Emphasis`.
+    ///
+    /// The samples are stored with the emphasis applied, so a player must apply the inverse
+    /// de-emphasis filter to get the proper audio. Reserved values are kept in `Unknown`.
+    EmphasisKind {
+        /// No emphasis.
+        None = 0,
+        /// CD audio.
+        CdAudio = 1,
+        /// CCIT J.17.
+        CcitJ17 = 3,
+        /// FM 50.
+        Fm50 = 4,
+        /// FM 75.
+        Fm75 = 5,
+        /// Phono RIAA.
+        PhonoRiaa = 10,
+        /// Phono IEC N78.
+        PhonoIecN78 = 11,
+        /// Phono TELDEC.
+        PhonoTeldec = 12,
+        /// Phono EMI.
+        PhonoEmi = 13,
+        /// Phono Columbia LP.
+        PhonoColumbiaLp = 14,
+        /// Phono LONDON.
+        PhonoLondon = 15,
+        /// Phono NARTB.
+        PhonoNartb = 16,
+    }
+}
+
+impl Emphasis {
+    /// The emphasis this value describes.
+    pub fn kind(&self) -> EmphasisKind {
+        self.0.into()
+    }
+}
+
+impl Audio {
+    /// The real output sampling frequency in Hz, which defaults to the `SamplingFrequency`.
+    ///
+    /// Both differ with SBR techniques, e.g. HE-AAC, where the output is twice the base frequency.
+    pub fn output_sampling_frequency_effective(&self) -> f64 {
+        self.output_sampling_frequency
+            .map_or(*self.sampling_frequency, |f| *f)
+    }
+
+    /// The emphasis applied on the samples, which players must invert, see [`EmphasisKind`].
+    pub fn emphasis_kind(&self) -> EmphasisKind {
+        self.emphasis.kind()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EmphasisKind;
+    use crate::leaf::*;
+    use crate::master::Audio;
+
+    #[test]
+    fn test_output_sampling_frequency_effective() {
+        let mut audio = Audio {
+            sampling_frequency: SamplingFrequency(24000.0),
+            ..Default::default()
+        };
+        assert_eq!(audio.output_sampling_frequency_effective(), 24000.0);
+
+        audio.output_sampling_frequency = Some(OutputSamplingFrequency(48000.0));
+        assert_eq!(audio.output_sampling_frequency_effective(), 48000.0);
+    }
+
+    #[test]
+    fn test_emphasis_kind() {
+        let mut audio = Audio::default();
+        assert_eq!(audio.emphasis_kind(), EmphasisKind::None);
+        audio.emphasis = Emphasis(1);
+        assert_eq!(audio.emphasis_kind(), EmphasisKind::CdAudio);
+        audio.emphasis = Emphasis(10);
+        assert_eq!(audio.emphasis_kind(), EmphasisKind::PhonoRiaa);
+        // reserved
+        audio.emphasis = Emphasis(2);
+        assert_eq!(audio.emphasis_kind(), EmphasisKind::Unknown(2));
+    }
+}
@@ -8,7 +8,9 @@ use std::fmt::Display;
 use std::ops::Deref;
 
 /// A variable-length integer RFC 8794
-#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+///
+/// VInt64s are ordered by their decoded value, with unknown sizes sorting after every known one.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct VInt64 {
     /// The decoded integer value.
     pub value: u64,
@@ -16,10 +18,25 @@ pub struct VInt64 {
     pub is_unknown: bool,
 }
 
+impl Ord for VInt64 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.is_unknown, self.value).cmp(&(other.is_unknown, other.value))
+    }
+}
+
+impl PartialOrd for VInt64 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Display for VInt64 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // write!(f, "{}", self.as_encoded())
-        let encoded = self.as_encoded();
+        let Ok(encoded) = self.as_encoded() else {
+            // too large for an EBML ID, only the value can be shown
+            return write!(f, "{}", self.value);
+        };
         if encoded <= 0xFF {
             write!(f, "0x{:02X}", encoded)
         } else if encoded <= 0xFFFF {
@@ -52,6 +69,33 @@ impl Debug for VInt64 {
     }
 }
 
+impl std::str::FromStr for VInt64 {
+    type Err = Error;
+
+    /// Parse an *encoded* VInt64, such as an element ID, from a `0x`-prefixed hex or a decimal string.
+    ///
+    /// This matches [`VInt64::from_encoded`], and is the inverse of the `Display` implementation
+    /// for the values that can be encoded on 8 bytes. Strings that are not the shortest encoding of
+    /// a value, such as `0x03` with no length marker or the plain value `Display` falls back to,
+    /// are rejected.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let encoded = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => s.parse::<u64>(),
+        }
+        .map_err(|_| Error::InvalidVIntString(s.to_string()))?;
+        if encoded == 0 {
+            return Err(Error::InvalidVIntString(s.to_string()));
+        }
+        let vint = VInt64::from_encoded(encoded);
+        if vint.as_encoded().ok() != Some(encoded) {
+            return Err(Error::InvalidVIntString(s.to_string()));
+        }
+        Ok(vint)
+    }
+}
+
 impl Deref for VInt64 {
     type Target = u64;
     fn deref(&self) -> &Self::Target {
@@ -66,11 +110,6 @@ impl VInt64 {
                 value: 127,
                 is_unknown: true,
             }
-        } else if enc == 0x407F {
-            Self {
-                value: 127,
-                is_unknown: false,
-            }
         } else {
             Self {
                 value: enc & (u64::MAX >> (enc.leading_zeros() + 1)),
@@ -95,57 +134,69 @@ impl VInt64 {
         }
     }
 
-    /// Create a VInt64 from an already encoded u64 value.
-    pub fn as_encoded(&self) -> u64 {
+    /// The encoded form of the value as a u64, the inverse of [`VInt64::from_encoded`].
+    ///
+    /// Returns [`Error::VIntOverflow`] for values of 2^56 - 1 and above, whose encoding
+    /// does not fit in 8 bytes.
+    pub fn as_encoded(&self) -> crate::Result<u64> {
         if self.is_unknown {
-            return 0xFF;
-        }
-        if self.value == 127 {
-            return 0x407F;
+            return Ok(0xFF);
         }
-
         let size = VInt64::encode_size(self.value);
+        if size > 8 {
+            return Err(Error::VIntOverflow {
+                value: self.value,
+                width: 8,
+            });
+        }
         let mut sbuf = [0u8; 8];
         let slice = &mut sbuf[8 - size..];
         slice.copy_from_slice(&self.value.to_be_bytes()[8 - size..]);
         slice[0] |= 1u8 << (8 - size);
-        u64::from_be_bytes(sbuf)
+        Ok(u64::from_be_bytes(sbuf))
     }
 
     /// Get the size in bytes of the encoded representation of a u64 value.
+    ///
+    /// A width of `n` bytes holds values below `2^(7n) - 1`, as the value with all bits set
+    /// is reserved for the unknown size: 126 fits in 1 byte, but 127 needs 2.
     pub const fn encode_size(value: u64) -> usize {
-        let leading_zeros = value.leading_zeros() as usize;
-        let total_bits = 64 - leading_zeros;
-        if total_bits == 0 {
-            1
-        } else {
-            (total_bits + 6).div_euclid(7)
-        }
+        // smallest width where value + 1 fits in the value bits
+        let total_bits = 128 - (value as u128 + 1).leading_zeros() as usize;
+        (total_bits + 6).div_euclid(7)
     }
 }
 
-impl ReadFrom for VInt64 {
-    fn read_from<R: std::io::Read + ?Sized>(r: &mut R) -> crate::Result<Self> {
-        let mut first_byte_buf = [0u8; 1];
-        r.read_exact(&mut first_byte_buf)?;
-        let first_byte = first_byte_buf[0];
+impl VInt64 {
+    /// Read a VInt64 from a reader, also returning the width of its encoding in bytes.
+    pub(crate) fn read_with_width<R: std::io::Read + ?Sized>(
+        r: &mut R,
+    ) -> crate::Result<(Self, usize)> {
+        use crate::io::ReadExt;
+        let first_byte = r.read_u8()?;
+        // a zero first byte would mean a VINT of 9 bytes or more
+        if first_byte == 0 {
+            return Err(crate::error::Error::InvalidVInt);
+        }
         if first_byte == 0xFF {
-            return Ok(VInt64 {
-                value: 127,
-                is_unknown: true,
-            });
+            return Ok((
+                VInt64 {
+                    value: 127,
+                    is_unknown: true,
+                },
+                1,
+            ));
         }
 
         let leading_zeros = first_byte.leading_zeros() as usize;
-        if leading_zeros >= 8 {
-            return Err(crate::error::Error::InvalidVInt);
-        }
-
         if leading_zeros == 0 {
-            Ok(VInt64 {
-                value: (first_byte & 0b0111_1111) as u64,
-                is_unknown: false,
-            })
+            Ok((
+                VInt64 {
+                    value: (first_byte & 0b0111_1111) as u64,
+                    is_unknown: false,
+                },
+                1,
+            ))
         } else {
             let mut buf = [0u8; 8];
             let read_buf = &mut buf[8 - leading_zeros..];
@@ -153,40 +204,62 @@ impl ReadFrom for VInt64 {
             if leading_zeros != 7 {
                 buf[8 - leading_zeros - 1] = first_byte & (0xFF >> (leading_zeros + 1));
             }
-            Ok(VInt64 {
-                value: u64::from_be_bytes(buf),
-                is_unknown: false,
-            })
+            Ok((
+                VInt64 {
+                    value: u64::from_be_bytes(buf),
+                    is_unknown: false,
+                },
+                leading_zeros + 1,
+            ))
         }
     }
-}
 
-#[cfg(feature = "tokio")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
-impl crate::io::tokio_impl::AsyncReadFrom for VInt64 {
-    async fn async_read_from<R: tokio::io::AsyncRead + Unpin + ?Sized>(
+    /// Read a VInt64 from a reader, rejecting encodings wider than needed.
+    ///
+    /// Fails with [`Error::NonMinimalVInt`] if the value could have been encoded in fewer bytes,
+    /// for strict conformance checking. The 1-byte unknown size is always minimal.
+    pub fn read_minimal<R: std::io::Read + ?Sized>(r: &mut R) -> crate::Result<Self> {
+        let (vint, width) = VInt64::read_with_width(r)?;
+        if !vint.is_unknown && width > VInt64::encode_size(vint.value) {
+            return Err(Error::NonMinimalVInt {
+                value: vint.value,
+                width,
+            });
+        }
+        Ok(vint)
+    }
+
+    /// Read a VInt64 from an async reader, also returning the width of its encoding in bytes.
+    #[cfg(feature = "tokio")]
+    pub(crate) async fn async_read_with_width<R: tokio::io::AsyncRead + Unpin + ?Sized>(
         r: &mut R,
-    ) -> crate::Result<Self> {
+    ) -> crate::Result<(Self, usize)> {
         let mut first_byte_buf = [0u8; 1];
         tokio::io::AsyncReadExt::read_exact(r, &mut first_byte_buf).await?;
         let first_byte = first_byte_buf[0];
+        // a zero first byte would mean a VINT of 9 bytes or more
+        if first_byte == 0 {
+            return Err(crate::error::Error::InvalidVInt);
+        }
         if first_byte == 0xFF {
-            return Ok(VInt64 {
-                value: 127,
-                is_unknown: true,
-            });
+            return Ok((
+                VInt64 {
+                    value: 127,
+                    is_unknown: true,
+                },
+                1,
+            ));
         }
 
         let leading_zeros = first_byte.leading_zeros() as usize;
-        if leading_zeros >= 8 {
-            return Err(crate::error::Error::InvalidVInt);
-        }
-
         if leading_zeros == 0 {
-            Ok(VInt64 {
-                value: (first_byte & 0b0111_1111) as u64,
-                is_unknown: false,
-            })
+            Ok((
+                VInt64 {
+                    value: (first_byte & 0b0111_1111) as u64,
+                    is_unknown: false,
+                },
+                1,
+            ))
         } else {
             let mut buf = [0u8; 8];
             let read_buf = &mut buf[8 - leading_zeros..];
@@ -194,12 +267,58 @@ impl crate::io::tokio_impl::AsyncReadFrom for VInt64 {
             if leading_zeros != 7 {
                 buf[8 - leading_zeros - 1] = first_byte & (0xFF >> (leading_zeros + 1));
             }
-            Ok(VInt64 {
-                value: u64::from_be_bytes(buf),
-                is_unknown: false,
-            })
+            Ok((
+                VInt64 {
+                    value: u64::from_be_bytes(buf),
+                    is_unknown: false,
+                },
+                leading_zeros + 1,
+            ))
         }
     }
+
+    /// Encode the VInt64 using exactly `width` bytes, instead of the minimal width.
+    ///
+    /// Fails with [`Error::VIntOverflow`] if the value does not fit in `width` bytes.
+    pub fn encode_with_width<B: BufMut>(&self, width: usize, buf: &mut B) -> crate::Result<()> {
+        if !(1..=8).contains(&width) {
+            return Err(Error::VIntOverflow {
+                value: self.value,
+                width,
+            });
+        }
+        // all value bits set is reserved for the unknown size
+        let reserved = (1u64 << (7 * width)) - 1;
+        let value = if self.is_unknown {
+            reserved
+        } else if self.value >= reserved {
+            return Err(Error::VIntOverflow {
+                value: self.value,
+                width,
+            });
+        } else {
+            self.value
+        };
+        let bytes = (value | (1u64 << (7 * width))).to_be_bytes();
+        buf.put_slice(&bytes[8 - width..]);
+        Ok(())
+    }
+}
+
+impl ReadFrom for VInt64 {
+    fn read_from<R: std::io::Read + ?Sized>(r: &mut R) -> crate::Result<Self> {
+        Ok(VInt64::read_with_width(r)?.0)
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+impl crate::io::tokio_impl::AsyncReadFrom for VInt64 {
+    async fn async_read_from<R: tokio::io::AsyncRead + Unpin + ?Sized>(
+        r: &mut R,
+    ) -> crate::Result<Self> {
+        Ok(VInt64::async_read_with_width(r).await?.0)
+    }
 }
 
 impl Decode for VInt64 {
@@ -245,12 +364,13 @@ impl Encode for VInt64 {
             buf.put_slice(&[0xFF]);
             return Ok(());
         }
-        if self.value == 127 {
-            buf.put_slice(&[0x40, 0x7F]);
-            return Ok(());
-        }
-
         let size = VInt64::encode_size(self.value);
+        if size > 8 {
+            return Err(Error::VIntOverflow {
+                value: self.value,
+                width: 8,
+            });
+        }
         let mut sbuf = [0u8; 8];
         let slice = &mut sbuf[8 - size..];
         slice.copy_from_slice(&self.value.to_be_bytes()[8 - size..]);
@@ -265,6 +385,44 @@ mod tests {
     use super::*;
     use std::convert::TryInto;
 
+    #[test]
+    fn test_read_from_zero_first_byte() {
+        let data = [0x00, 0x81, 0x81, 0x81, 0x81, 0x81, 0x81, 0x81, 0x81];
+        assert!(matches!(
+            VInt64::read_from(&mut &data[..]),
+            Err(Error::InvalidVInt)
+        ));
+        assert!(matches!(
+            VInt64::decode(&mut &data[..]),
+            Err(Error::InvalidVInt)
+        ));
+    }
+
+    #[test]
+    fn test_from_str() {
+        use crate::element::Element;
+        use crate::master::Segment;
+        let display = Segment::ID.to_string();
+        assert_eq!(display, "0x18538067");
+        assert_eq!(display.parse::<VInt64>().unwrap(), Segment::ID);
+        assert_eq!("408125543".parse::<VInt64>().unwrap(), Segment::ID);
+        assert_eq!(
+            "0xEC".parse::<VInt64>().unwrap(),
+            VInt64::from_encoded(0xEC)
+        );
+        assert!("0x".parse::<VInt64>().is_err());
+        assert!("0".parse::<VInt64>().is_err());
+        assert!("segment".parse::<VInt64>().is_err());
+        // no length marker where the encoding expects it, or a longer encoding than needed
+        assert!("0x03".parse::<VInt64>().is_err());
+        assert!("0x4001".parse::<VInt64>().is_err());
+        // values too large to encode are displayed as is, and cannot be parsed back
+        let wide = VInt64::new(1 << 57);
+        assert_eq!(wide.to_string(), (1u64 << 57).to_string());
+        assert!(wide.to_string().parse::<VInt64>().is_err());
+        assert_eq!("0xFF".parse::<VInt64>().unwrap(), VInt64::new_unknown());
+    }
+
     #[test]
     fn test_encode_size() {
         let test_pair = [
@@ -272,20 +430,52 @@ mod tests {
             (vec![0b1000_0001], 1),
             (vec![0b0100_0000, 0xFF], 0xFF),
             (vec![0b0100_0001, 0xFF], 0b1_1111_1111),
-            (vec![0b0111_1111, 0xFF], 0b11_1111_1111_1111),
+            (vec![0b0111_1111, 0xFE], 0b11_1111_1111_1110),
+            // all value bits set is reserved for the unknown size
+            (vec![0b0010_0000, 0b0011_1111, 0xFF], 0b11_1111_1111_1111),
             (vec![0b0010_0000, 0b0111_1111, 0xFF], 0b111_1111_1111_1111),
             (vec![0b0010_0000, 0xFF, 0xFF], 0xFFFF),
-            (vec![0b0011_1111, 0xFF, 0xFF], 0b1_1111_1111_1111_1111_1111),
+            (vec![0b0011_1111, 0xFF, 0xFE], 0b1_1111_1111_1111_1111_1110),
             (
-                vec![1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
-                0xFF_FFFF_FFFF_FFFF,
+                vec![1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE],
+                0xFF_FFFF_FFFF_FFFE,
             ),
         ];
         for (encoded, val) in test_pair {
             assert_eq!(VInt64::encode_size(val), encoded.len());
+            let mut buf = vec![];
+            VInt64::new(val).encode(&mut buf).unwrap();
+            assert_eq!(buf, encoded);
         }
     }
 
+    #[test]
+    fn test_encode_size_boundaries() {
+        for width in 1..=8u32 {
+            // the largest value of a width, and the first one needing the next width
+            let max = (1u64 << (7 * width)) - 2;
+            for (value, size) in [(max, width as usize), (max + 1, width as usize + 1)] {
+                assert_eq!(VInt64::encode_size(value), size, "value {value}");
+                let mut buf = vec![];
+                let result = VInt64::new(value).encode(&mut buf);
+                if size > 8 {
+                    assert!(matches!(result, Err(Error::VIntOverflow { .. })));
+                    continue;
+                }
+                result.unwrap();
+                assert_eq!(buf.len(), size, "value {value}");
+                let decoded = VInt64::decode(&mut &buf[..]).unwrap();
+                assert_eq!(decoded, VInt64::new(value));
+            }
+        }
+        assert_eq!(VInt64::encode_size(0), 1);
+        assert_eq!(VInt64::encode_size(126), 1);
+        assert_eq!(VInt64::encode_size(127), 2);
+        assert_eq!(VInt64::encode_size(128), 2);
+        assert_eq!(VInt64::encode_size(16383), 3);
+        assert_eq!(VInt64::encode_size(16384), 3);
+    }
+
     #[test]
     fn test_encode() {
         let test_pair = [
@@ -293,13 +483,15 @@ mod tests {
             (vec![0b1000_0001], 1),
             (vec![0b0100_0000, 0xFF], 0xFF),
             (vec![0b0100_0001, 0xFF], 0b1_1111_1111),
-            (vec![0b0111_1111, 0xFF], 0b11_1111_1111_1111),
+            (vec![0b0111_1111, 0xFE], 0b11_1111_1111_1110),
+            // all value bits set is reserved for the unknown size
+            (vec![0b0010_0000, 0b0011_1111, 0xFF], 0b11_1111_1111_1111),
             (vec![0b0010_0000, 0b0111_1111, 0xFF], 0b111_1111_1111_1111),
             (vec![0b0010_0000, 0xFF, 0xFF], 0xFFFF),
-            (vec![0b0011_1111, 0xFF, 0xFF], 0b1_1111_1111_1111_1111_1111),
+            (vec![0b0011_1111, 0xFF, 0xFE], 0b1_1111_1111_1111_1111_1110),
             (
-                vec![1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
-                0xFF_FFFF_FFFF_FFFF,
+                vec![1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE],
+                0xFF_FFFF_FFFF_FFFE,
             ),
         ];
         for (encoded, val) in test_pair {
@@ -311,7 +503,7 @@ mod tests {
             v.encode(&mut out).unwrap();
             assert_eq!(encoded, out);
 
-            let encoded_num = v.as_encoded();
+            let encoded_num = v.as_encoded().unwrap();
             let mut enc8 = vec![0u8; 8 - encoded.len()];
             enc8.extend_from_slice(&encoded);
             let encoded_from = u64::from_be_bytes(enc8.try_into().unwrap());
@@ -326,10 +518,18 @@ mod tests {
             (vec![0b1000_0001], 1),
             (vec![0b0100_0000, 0xFF], 0xFF),
             (vec![0b0100_0001, 0xFF], 0b1_1111_1111),
+            (vec![0b0111_1111, 0xFE], 0b11_1111_1111_1110),
+            // all value bits set, reserved for the unknown size but still decodable
             (vec![0b0111_1111, 0xFF], 0b11_1111_1111_1111),
+            (vec![0b0010_0000, 0b0011_1111, 0xFF], 0b11_1111_1111_1111),
             (vec![0b0010_0000, 0b0111_1111, 0xFF], 0b111_1111_1111_1111),
             (vec![0b0010_0000, 0xFF, 0xFF], 0xFFFF),
+            (vec![0b0011_1111, 0xFF, 0xFE], 0b1_1111_1111_1111_1111_1110),
             (vec![0b0011_1111, 0xFF, 0xFF], 0b1_1111_1111_1111_1111_1111),
+            (
+                vec![1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE],
+                0xFF_FFFF_FFFF_FFFE,
+            ),
             (
                 vec![1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
                 0xFF_FFFF_FFFF_FFFF,
@@ -374,24 +574,86 @@ mod tests {
         assert_eq!(*vv3, 127);
 
         assert_ne!(VInt64::new(127), VInt64::new_unknown());
-        assert_eq!(VInt64::new(127).as_encoded(), 0x407F);
+        assert_eq!(VInt64::new(127).as_encoded().unwrap(), 0x407F);
     }
 }
 
 /// EBML element header, consisting of an ID and a size.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+///
+/// Headers are compared, ordered and hashed by ID and size only, the width of the encoded size
+/// is a matter of layout.
+#[derive(Debug, Copy, Clone)]
 pub struct Header {
     /// EBML ID of the element.
     pub id: VInt64,
     /// Size of the element's data, excluding the header itself.
     pub size: VInt64,
+    /// Width in bytes of the encoded size, see [`Header::size_width()`].
+    pub size_width: Option<usize>,
+}
+
+impl PartialEq for Header {
+    fn eq(&self, other: &Self) -> bool {
+        (self.id, self.size) == (other.id, other.size)
+    }
+}
+
+impl Eq for Header {}
+
+impl std::hash::Hash for Header {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (self.id, self.size).hash(state);
+    }
+}
+
+impl PartialOrd for Header {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Header {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.id, self.size).cmp(&(other.id, other.size))
+    }
+}
+
+impl Header {
+    /// Create a header with the given ID and size, using the minimal size encoding.
+    pub const fn new(id: VInt64, size: VInt64) -> Self {
+        Self {
+            id,
+            size,
+            size_width: None,
+        }
+    }
+
+    /// This header, with its size encoded on exactly `width` bytes instead of the minimal width.
+    pub const fn with_size_width(self, width: usize) -> Self {
+        Self {
+            size_width: Some(width),
+            ..self
+        }
+    }
+
+    /// Width in bytes of the encoded size, recorded when reading the header.
+    ///
+    /// When set, the size is encoded with exactly this width instead of the minimal one,
+    /// allowing a byte-exact round-trip of non-minimal size encodings. `None` means minimal encoding.
+    pub const fn size_width(&self) -> Option<usize> {
+        self.size_width
+    }
 }
 
 impl ReadFrom for Header {
     fn read_from<R: std::io::Read + ?Sized>(reader: &mut R) -> crate::Result<Self> {
         let id = VInt64::read_from(reader)?;
-        let size = VInt64::read_from(reader)?;
-        Ok(Self { id, size })
+        let (size, size_width) = VInt64::read_with_width(reader)?;
+        Ok(Self {
+            id,
+            size,
+            size_width: Some(size_width),
+        })
     }
 }
 
@@ -402,23 +664,169 @@ impl crate::io::tokio_impl::AsyncReadFrom for Header {
         r: &mut R,
     ) -> crate::Result<Self> {
         let id = VInt64::async_read_from(r).await?;
-        let size = VInt64::async_read_from(r).await?;
-        Ok(Self { id, size })
+        let (size, size_width) = VInt64::async_read_with_width(r).await?;
+        Ok(Self {
+            id,
+            size,
+            size_width: Some(size_width),
+        })
     }
 }
 
 impl Decode for Header {
     fn decode(buf: &mut dyn Buf) -> crate::Result<Self> {
         let id = VInt64::decode(buf)?;
+        let remaining = buf.remaining();
         let size = VInt64::decode(buf)?;
-        Ok(Self { id, size })
+        Ok(Self {
+            id,
+            size,
+            size_width: Some(remaining - buf.remaining()),
+        })
     }
 }
 
 impl Encode for Header {
     fn encode<B: BufMut>(&self, buf: &mut B) -> crate::Result<()> {
         self.id.encode(buf)?;
-        self.size.encode(buf)?;
+        match self.size_width {
+            Some(width) => self.size.encode_with_width(width, buf)?,
+            None => self.size.encode(buf)?,
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod header_tests {
+    use super::*;
+
+    #[test]
+    fn test_size_width_round_trip() {
+        // Void element with a body of 3 bytes, size encoded with 8 bytes instead of 1
+        let encoded = [0xEC, 0x01, 0, 0, 0, 0, 0, 0, 0x03];
+        let header = Header::read_from(&mut &encoded[..]).unwrap();
+        assert_eq!(*header.size, 3);
+        assert_eq!(header.size_width, Some(8));
+        let mut out = vec![];
+        header.encode(&mut out).unwrap();
+        assert_eq!(out, encoded);
+
+        let header = Header::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(header.size_width, Some(8));
+
+        // the width is not part of the value
+        assert_eq!(header, Header::new(header.id, header.size));
+
+        // minimal encoding when the width is not recorded
+        let mut out = vec![];
+        Header::new(header.id, header.size)
+            .encode(&mut out)
+            .unwrap();
+        assert_eq!(out, [0xEC, 0x83]);
+
+        // value does not fit in the requested width
+        let header = Header::new(VInt64::from_encoded(0xEC), VInt64::new(200)).with_size_width(1);
+        assert!(matches!(
+            header.encode(&mut vec![]),
+            Err(Error::VIntOverflow {
+                value: 200,
+                width: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_read_minimal() {
+        assert_eq!(*VInt64::read_minimal(&mut &[0x85][..]).unwrap(), 5);
+        assert!(matches!(
+            VInt64::read_minimal(&mut &[0x40, 0x05][..]),
+            Err(Error::NonMinimalVInt { value: 5, width: 2 })
+        ));
+        // 127 is reserved on 1 byte, so 2 bytes are minimal
+        assert_eq!(*VInt64::read_minimal(&mut &[0x40, 0x7F][..]).unwrap(), 127);
+        assert!(VInt64::read_minimal(&mut &[0xFF][..]).unwrap().is_unknown);
+    }
+
+    #[test]
+    fn test_skip() {
+        use std::io::{Cursor, Seek};
+        // Void element with a body of 3 bytes, followed by a 1 byte Void
+        let encoded = [0xEC, 0x83, 1, 2, 3, 0xEC, 0x80];
+        let mut reader = Cursor::new(&encoded[..]);
+        let header = Header::read_from(&mut reader).unwrap();
+        let body_position = reader.stream_position().unwrap();
+        header.skip(&mut reader).unwrap();
+        assert_eq!(
+            reader.stream_position().unwrap(),
+            body_position + *header.size
+        );
+
+        let mut reader = &encoded[..];
+        let header = Header::read_from(&mut reader).unwrap();
+        header.skip_read(&mut reader).unwrap();
+        assert_eq!(reader, [0xEC, 0x80]);
+
+        // body shorter than the size
+        let mut reader = &encoded[..4];
+        let header = Header::read_from(&mut reader).unwrap();
+        assert!(header.skip_read(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_ord() {
+        let unknown = VInt64::new_unknown();
+        assert!(VInt64::new(127) < unknown);
+        assert!(VInt64::new(200) < unknown);
+        assert!(VInt64::new(1) < VInt64::new(2));
+        assert_eq!(
+            unknown.cmp(&VInt64::new_unknown()),
+            std::cmp::Ordering::Equal
+        );
+
+        let mut sizes = vec![unknown, VInt64::new(300), VInt64::new(127), VInt64::new(5)];
+        sizes.sort();
+        assert_eq!(
+            sizes,
+            vec![VInt64::new(5), VInt64::new(127), VInt64::new(300), unknown]
+        );
+    }
+
+    #[test]
+    fn test_encoded_round_trip() {
+        // all values up to 3 bytes, and around the limit of every width
+        let boundaries = (1..=8u32).flat_map(|n| {
+            let limit = (1u64 << (7 * n)) - 1;
+            limit.saturating_sub(2)..=limit.min((1 << 56) - 2)
+        });
+        for value in (0..(1 << 21)).chain(boundaries) {
+            let v = VInt64::new(value);
+            let encoded = v.as_encoded().unwrap();
+            assert_eq!(VInt64::from_encoded(encoded), v, "{value}");
+            // minimal: the marker bit is in the first byte of the shortest width
+            let width = VInt64::encode_size(value);
+            assert_eq!(encoded.leading_zeros() as usize / 8, 8 - width, "{value}");
+            assert_eq!(encoded >> (8 * width - width), 1, "{value}");
+        }
+
+        assert_eq!(VInt64::new(127).as_encoded().unwrap(), 0x407F);
+        assert_eq!(VInt64::from_encoded(0x407F), VInt64::new(127));
+        assert_eq!(VInt64::new_unknown().as_encoded().unwrap(), 0xFF);
+        assert_eq!(VInt64::from_encoded(0xFF), VInt64::new_unknown());
+        assert_ne!(VInt64::from_encoded(0x407F), VInt64::from_encoded(0xFF));
+
+        use crate::element::Element;
+        use crate::master::{Cluster, Ebml, Segment};
+        assert_eq!(Ebml::ID.as_encoded().unwrap(), 0x1A45DFA3);
+        assert_eq!(Segment::ID.as_encoded().unwrap(), 0x18538067);
+        assert_eq!(Cluster::ID.as_encoded().unwrap(), 0x1F43B675);
+        assert_eq!(crate::leaf::EbmlVersion::ID.as_encoded().unwrap(), 0x4286);
+
+        // the encoding of the largest values does not fit in 8 bytes
+        assert!(matches!(
+            VInt64::new((1 << 56) - 1).as_encoded(),
+            Err(Error::VIntOverflow { width: 8, .. })
+        ));
+        assert_eq!(VInt64::new(u64::MAX).to_string(), u64::MAX.to_string());
+    }
+}
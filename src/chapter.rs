@@ -0,0 +1,245 @@
+//! Helpers for working with chapters.
+
+use std::collections::HashSet;
+
+use crate::Error;
+use crate::master::{ChapProcess, ChapterAtom, ChapterDisplay, Chapters, EditionEntry};
+
+impl ChapterDisplay {
+    /// Languages of the display, the `ChapLanguageBCP47` ones when present,
+    /// otherwise the legacy `ChapLanguage` ones, which default to "eng".
+    pub fn languages(&self) -> Vec<&str> {
+        if !self.chap_language_bcp47.is_empty() {
+            self.chap_language_bcp47
+                .iter()
+                .map(|l| l.0.as_str())
+                .collect()
+        } else if !self.chap_language.is_empty() {
+            self.chap_language.iter().map(|l| l.0.as_str()).collect()
+        } else {
+            vec!["eng"]
+        }
+    }
+}
+
+impl ChapterAtom {
+    /// The chapter string to display for the requested language, e.g. "fr" or "pt-BR".
+    ///
+    /// An exact (case-insensitive) language match is preferred, then a match on the primary
+    /// language subtag, e.g. "fr-CA" for "fr". Falls back to the first display.
+    pub fn display_for(&self, lang: &str) -> Option<&str> {
+        let primary = |l: &str| l.split('-').next().unwrap_or(l).to_ascii_lowercase();
+        let exact = self
+            .chapter_display
+            .iter()
+            .find(|d| d.languages().iter().any(|l| l.eq_ignore_ascii_case(lang)));
+        let same_primary = || {
+            self.chapter_display
+                .iter()
+                .find(|d| d.languages().iter().any(|l| primary(l) == primary(lang)))
+        };
+        exact
+            .or_else(same_primary)
+            .or_else(|| self.chapter_display.first())
+            .map(|d| d.chap_string.0.as_str())
+    }
+}
+
+impl<'a> IntoIterator for &'a Chapters {
+    type Item = &'a EditionEntry;
+    type IntoIter = std::slice::Iter<'a, EditionEntry>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.edition_entry.iter()
+    }
+}
+
+impl Chapters {
+    /// Iterate over the editions, see also `&Chapters` which is iterable.
+    pub fn iter(&self) -> std::slice::Iter<'_, EditionEntry> {
+        self.edition_entry.iter()
+    }
+
+    /// The edition a player should use: the first one flagged as default, otherwise the first one.
+    pub fn default_edition(&self) -> Option<&EditionEntry> {
+        self.edition_entry
+            .iter()
+            .find(|e| *e.edition_flag_default != 0)
+            .or_else(|| self.edition_entry.first())
+    }
+
+    /// Check the invariants of the chapters, across all editions and nested atoms.
+    ///
+    /// `ChapterUID`s must be unique, and `ChapterTimeEnd` must not be before `ChapterTimeStart`.
+    /// In ordered editions, `ChapterTimeEnd` is also required.
+    pub fn validate(&self) -> crate::Result<()> {
+        fn validate_atom(
+            atom: &ChapterAtom,
+            ordered: bool,
+            uids: &mut HashSet<u64>,
+        ) -> crate::Result<()> {
+            let uid = *atom.chapter_uid;
+            let invalid = |detail: String| Error::InvalidChapter { detail };
+            if !uids.insert(uid) {
+                return Err(invalid(format!("duplicate ChapterUID {uid}")));
+            }
+            match atom.chapter_time_end {
+                Some(end) if *end < *atom.chapter_time_start => {
+                    return Err(invalid(format!(
+                        "chapter {uid} ends at {} before it starts at {}",
+                        *end, *atom.chapter_time_start
+                    )));
+                }
+                None if ordered => {
+                    return Err(invalid(format!(
+                        "chapter {uid} of an ordered edition has no ChapterTimeEnd"
+                    )));
+                }
+                _ => {}
+            }
+            atom.chapter_atom
+                .iter()
+                .try_for_each(|child| validate_atom(child, ordered, uids))
+        }
+
+        let mut uids = HashSet::new();
+        for edition in &self.edition_entry {
+            for atom in &edition.chapter_atom {
+                validate_atom(atom, edition.is_ordered(), &mut uids)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl EditionEntry {
+    /// Whether any chapter of the edition, at any depth, uses the DVD command set.
+    pub(crate) fn has_dvd_process(&self) -> bool {
+        fn atom_has_dvd_process(atom: &ChapterAtom) -> bool {
+            atom.chap_process.iter().any(ChapProcess::is_dvd)
+                || atom.chapter_atom.iter().any(atom_has_dvd_process)
+        }
+        self.chapter_atom.iter().any(atom_has_dvd_process)
+    }
+
+    /// Whether the edition is ordered, i.e. its chapters are played in order as segments
+    /// of a virtual timeline, rather than being mere markers.
+    pub fn is_ordered(&self) -> bool {
+        *self.edition_flag_ordered != 0
+    }
+}
+
+impl ChapProcess {
+    /// Whether the commands use the DVD command set (`ChapProcessCodecID` 1).
+    pub fn is_dvd(&self) -> bool {
+        *self.chap_process_codec_id == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn display(s: &str, bcp47: &str) -> ChapterDisplay {
+        ChapterDisplay {
+            chap_string: ChapString(s.to_string()),
+            chap_language_bcp47: vec![ChapLanguageBcp47(bcp47.to_string())],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_display_for() {
+        let atom = ChapterAtom {
+            chapter_display: vec![
+                display("Opening", "en"),
+                display("Ouverture", "fr"),
+                display("Abertura", "pt-BR"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(atom.display_for("fr"), Some("Ouverture"));
+        assert_eq!(atom.display_for("FR"), Some("Ouverture"));
+        assert_eq!(atom.display_for("fr-CA"), Some("Ouverture"));
+        assert_eq!(atom.display_for("pt"), Some("Abertura"));
+        assert_eq!(atom.display_for("de"), Some("Opening"));
+        assert_eq!(ChapterAtom::default().display_for("en"), None);
+
+        let legacy = ChapterDisplay {
+            chap_string: ChapString("Kapitel".to_string()),
+            chap_language: vec![ChapLanguage("ger".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(legacy.languages(), vec!["ger"]);
+        assert_eq!(ChapterDisplay::default().languages(), vec!["eng"]);
+    }
+
+    #[test]
+    fn test_default_edition() {
+        let mut chapters = Chapters {
+            edition_entry: vec![
+                EditionEntry {
+                    edition_uid: Some(EditionUid(1)),
+                    ..Default::default()
+                },
+                EditionEntry {
+                    edition_uid: Some(EditionUid(2)),
+                    edition_flag_default: EditionFlagDefault(1),
+                    edition_flag_ordered: EditionFlagOrdered(1),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let edition = chapters.default_edition().unwrap();
+        assert_eq!(edition.edition_uid, Some(EditionUid(2)));
+        assert!(edition.is_ordered());
+
+        chapters.edition_entry[1].edition_flag_default = EditionFlagDefault(0);
+        let edition = chapters.default_edition().unwrap();
+        assert_eq!(edition.edition_uid, Some(EditionUid(1)));
+        assert!(!edition.is_ordered());
+
+        assert!(Chapters::default().default_edition().is_none());
+    }
+
+    fn atom(uid: u64, start: u64, end: Option<u64>) -> ChapterAtom {
+        ChapterAtom {
+            chapter_uid: ChapterUid(uid),
+            chapter_time_start: ChapterTimeStart(start),
+            chapter_time_end: end.map(ChapterTimeEnd),
+            ..Default::default()
+        }
+    }
+
+    fn chapters(atoms: Vec<ChapterAtom>, ordered: bool) -> Chapters {
+        Chapters {
+            edition_entry: vec![EditionEntry {
+                edition_flag_ordered: EditionFlagOrdered(ordered as u64),
+                chapter_atom: atoms,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut parent = atom(1, 0, Some(1000));
+        parent.chapter_atom = vec![atom(2, 0, Some(500)), atom(3, 500, Some(1000))];
+        assert!(chapters(vec![parent.clone()], true).validate().is_ok());
+
+        // duplicate UID in a nested atom
+        parent.chapter_atom.push(atom(1, 0, Some(10)));
+        let err = chapters(vec![parent], false).validate().unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidChapter { .. }), "{err}");
+
+        let err = chapters(vec![atom(1, 1000, Some(500))], false)
+            .validate()
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidChapter { .. }), "{err}");
+
+        // ChapterTimeEnd is only required in ordered editions
+        assert!(chapters(vec![atom(1, 0, None)], false).validate().is_ok());
+        assert!(chapters(vec![atom(1, 0, None)], true).validate().is_err());
+    }
+}
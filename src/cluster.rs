@@ -0,0 +1,619 @@
+//! Helpers for working with clusters.
+
+use crate::element::DecodeOptions;
+use crate::leaf::SimpleBlock;
+use crate::master::{BlockAdditions, BlockGroup, BlockMore, Cluster, Tracks};
+use crate::*;
+
+impl Cluster {
+    /// Shift the Cluster timestamp by `delta` ticks, moving every block in it by the same amount.
+    ///
+    /// Block timestamps are relative to the Cluster timestamp, so they are left untouched.
+    /// This is the key operation when appending the clusters of one Segment after another.
+    /// Returns [`Error::TimestampOutOfRange`] if the shifted timestamp would not fit in a `u64`,
+    /// leaving the Cluster unchanged.
+    pub fn shift_timestamp(&mut self, delta: i64) -> crate::Result<()> {
+        let shifted =
+            self.timestamp
+                .checked_add_signed(delta)
+                .ok_or(Error::TimestampOutOfRange {
+                    timestamp: *self.timestamp,
+                    delta,
+                })?;
+        self.timestamp.0 = shifted;
+        Ok(())
+    }
+
+    /// Combine adjacent Clusters into one, with the timestamp of the first, e.g. to cut the
+    /// overhead of pathologically tiny clusters when repackaging.
+    ///
+    /// The blocks are concatenated in order, their relative timestamps re-based on the new Cluster
+    /// timestamp so every frame keeps its absolute timestamp. Returns
+    /// [`Error::RelativeTimestampOutOfRange`] if a re-based timestamp does not fit in an `i16`.
+    /// `Position` and `PrevSize` are dropped as they no longer hold, and merging no Cluster gives
+    /// an empty one.
+    pub fn merge(clusters: &[Cluster]) -> crate::Result<Cluster> {
+        let Some(first) = clusters.first() else {
+            return Ok(Cluster::default());
+        };
+        let mut merged = Cluster {
+            timestamp: first.timestamp,
+            ..Default::default()
+        };
+        for cluster in clusters {
+            let offset = *cluster.timestamp as i128 - *first.timestamp as i128;
+            for block in &cluster.blocks {
+                let (_, relative) = block.header()?;
+                let rebased = offset + relative as i128;
+                let rebased =
+                    i16::try_from(rebased).map_err(|_| Error::RelativeTimestampOutOfRange {
+                        relative: rebased.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+                    })?;
+                merged.blocks.push(block.with_relative_timestamp(rebased)?);
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Text subtitles of a track in the Cluster, as `(start_ns, duration_ns, text)`.
+    ///
+    /// The start is converted from ticks with the Segment `TimestampScale`, and the duration comes
+    /// from the BlockDuration, which text subtitles are expected to carry. See [`Frame::as_text`].
+    pub fn subtitle_cues(
+        &self,
+        track: u64,
+        timestamp_scale: u64,
+    ) -> crate::Result<Vec<(i64, Option<u64>, &str)>> {
+        self.frames()
+            .filter(|frame| !matches!(frame, Ok(f) if f.track_number != track))
+            .map(|frame| {
+                let frame = frame?;
+                let duration = frame
+                    .duration
+                    .map(|d| {
+                        d.get()
+                            .checked_mul(timestamp_scale)
+                            .ok_or(Error::TimestampOverflow { timestamp_scale })
+                    })
+                    .transpose()?;
+                Ok((
+                    frame.timestamp.to_ns(timestamp_scale)?,
+                    duration,
+                    frame.as_text()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Write the Cluster with an unknown size, as in live streams where the size is not known upfront.
+    ///
+    /// See [`ClusterWriter`] to stream the blocks of a Cluster and back-patch its size instead.
+    pub fn write_unknown_size<W: std::io::Write + ?Sized>(&self, w: &mut W) -> crate::Result<()> {
+        use crate::base::{Header, VInt64};
+        use crate::element::Element;
+        use crate::io::blocking_impl::WriteElement;
+        self.write_element(&Header::new(Self::ID, VInt64::new_unknown()), w)
+    }
+
+    /// The Cluster timestamp in nanoseconds, given the Segment `TimestampScale`.
+    ///
+    /// Returns an error if the scale is 0 or the result does not fit in an `i64`, see [`Ticks::to_ns`].
+    pub fn timestamp_ns(&self, timestamp_scale: u64) -> crate::Result<i64> {
+        let ticks = i64::try_from(*self.timestamp)
+            .map_err(|_| Error::TimestampOverflow { timestamp_scale })?;
+        Ticks(ticks).to_ns(timestamp_scale)
+    }
+
+    /// Track number and absolute timestamp in nanoseconds of every block, in Cluster order.
+    ///
+    /// Only the block headers are read, so unlike [`Cluster::frames`] the lacing of the
+    /// payloads is never parsed.
+    pub fn block_timestamps_ns(
+        &self,
+        timestamp_scale: u64,
+    ) -> impl Iterator<Item = crate::Result<(u64, i64)>> + '_ {
+        self.blocks.iter().map(move |block| {
+            let (track_number, relative) = block.header()?;
+            let timestamp = i64::try_from(*self.timestamp)
+                .ok()
+                .and_then(|timestamp| timestamp.checked_add(relative as i64))
+                .ok_or(Error::TimestampOutOfRange {
+                    timestamp: *self.timestamp,
+                    delta: relative as i64,
+                })?;
+            Ok((track_number, Ticks(timestamp).to_ns(timestamp_scale)?))
+        })
+    }
+
+    /// The number of frames in the Cluster, counting each frame of a laced block.
+    ///
+    /// Only the block headers and lacing heads are read, the payloads are never delaced.
+    pub fn frame_count(&self) -> crate::Result<usize> {
+        self.blocks
+            .iter()
+            .try_fold(0, |total, block| Ok(total + block.frame_count()?.0))
+    }
+
+    /// The number of keyframes in the Cluster, counting each frame of a laced block,
+    /// with the same keyframe rules as [`Cluster::frames`]. See [`Cluster::frame_count`].
+    pub fn keyframe_count(&self) -> crate::Result<usize> {
+        self.blocks.iter().try_fold(0, |total, block| {
+            let (count, keyframe) = block.frame_count()?;
+            Ok(total + if keyframe { count } else { 0 })
+        })
+    }
+
+    /// Check that no block is laced on a track whose `FlagLacing` is 0.
+    ///
+    /// In strict mode such a block gives [`Error::UnexpectedLacing`], otherwise it is only logged,
+    /// as players read laced blocks regardless. Blocks of tracks missing from `tracks` are ignored.
+    pub fn validate_lacing(&self, tracks: &Tracks, options: &DecodeOptions) -> crate::Result<()> {
+        for block in &self.blocks {
+            let (track_number, _, flags) = read_block_header(&mut block.body())?;
+            let Some(track) = tracks.by_number(track_number) else {
+                continue;
+            };
+            if *track.flag_lacing == 0 && flags.lacing().is_some() {
+                if options.is_strict() {
+                    return Err(Error::UnexpectedLacing(track_number));
+                }
+                log::warn!("Laced block on track {track_number}, which does not allow lacing");
+            }
+        }
+        Ok(())
+    }
+
+    /// Append a SimpleBlock or a BlockGroup to the Cluster, after the existing blocks.
+    ///
+    /// Blocks of both kinds share one list, so their order is kept when encoding.
+    pub fn push_block(&mut self, block: impl Into<ClusterBlock>) {
+        self.blocks.push(block.into());
+    }
+}
+
+/// Read the track number, relative timestamp and flags of a Block, leaving `buf` at the payload.
+fn read_block_header(buf: &mut &[u8]) -> crate::Result<(u64, i16, BlockFlags)> {
+    use crate::base::VInt64;
+    use bytes::Buf;
+    let track_number = VInt64::decode(buf)?;
+    let timestamp = buf.try_get_i16()?;
+    let flags = BlockFlags::from_byte(buf.try_get_u8()?);
+    Ok((*track_number, timestamp, flags))
+}
+
+impl SimpleBlock {
+    /// The track number, timestamp relative to the Cluster, and flags of the block.
+    ///
+    /// Only the header is read, the payload is neither delaced nor copied.
+    pub fn header_info(&self) -> crate::Result<(u64, i16, BlockFlags)> {
+        read_block_header(&mut &self[..])
+    }
+}
+
+impl BlockGroup {
+    /// The track number, timestamp relative to the Cluster, and flags of the Block.
+    ///
+    /// Only the header is read, as in [`SimpleBlock::header_info`]. The keyframe and discardable
+    /// flags are not used in a Block, keyframes are the groups without a `ReferenceBlock`.
+    pub fn header_info(&self) -> crate::Result<(u64, i16, BlockFlags)> {
+        read_block_header(&mut &self.block[..])
+    }
+}
+
+impl ClusterBlock {
+    /// The body of the SimpleBlock or of the Block in the BlockGroup.
+    fn body(&self) -> &[u8] {
+        match self {
+            ClusterBlock::Simple(b) => b,
+            ClusterBlock::Group(g) => &g.block,
+        }
+    }
+
+    /// The track number and relative timestamp from the Block header, leaving the payload alone.
+    pub(crate) fn header(&self) -> crate::Result<(u64, i16)> {
+        let (track_number, timestamp, _) = read_block_header(&mut self.body())?;
+        Ok((track_number, timestamp))
+    }
+
+    /// A copy of the block with another timestamp relative to the Cluster, the rest untouched.
+    fn with_relative_timestamp(&self, timestamp: i16) -> crate::Result<ClusterBlock> {
+        let body = self.body();
+        let rest = &mut &body[..];
+        read_block_header(rest)?;
+        // the timestamp sits right before the flags byte, just ahead of the payload
+        let offset = body.len() - rest.len() - 3;
+        let mut retimed = body.to_vec();
+        retimed[offset..offset + 2].copy_from_slice(&timestamp.to_be_bytes());
+        Ok(match self {
+            ClusterBlock::Simple(_) => ClusterBlock::Simple(SimpleBlock(retimed.into())),
+            ClusterBlock::Group(g) => ClusterBlock::Group(BlockGroup {
+                block: crate::leaf::Block(retimed.into()),
+                ..g.clone()
+            }),
+        })
+    }
+
+    /// The number of frames in the block and whether they are keyframes, read from the lacing
+    /// head without delacing the payload.
+    fn frame_count(&self) -> crate::Result<(usize, bool)> {
+        let buf = &mut self.body();
+        let (_, _, flags) = read_block_header(buf)?;
+        let count = match flags.lacing() {
+            None => 1,
+            // an empty laced block has no frames, see `Lacer::delace`
+            Some(_) => buf.first().map_or(0, |&n| n as usize + 1),
+        };
+        let keyframe = match self {
+            ClusterBlock::Simple(_) => flags.keyframe(),
+            ClusterBlock::Group(g) => g.reference_block.is_empty(),
+        };
+        Ok((count, keyframe))
+    }
+}
+
+impl Extend<ClusterBlock> for Cluster {
+    /// Append the blocks after the existing ones, as [`Cluster::push_block`] does.
+    fn extend<I: IntoIterator<Item = ClusterBlock>>(&mut self, iter: I) {
+        self.blocks.extend(iter);
+    }
+}
+
+impl FromIterator<ClusterBlock> for Cluster {
+    /// Build a Cluster holding the blocks, with a Cluster timestamp of 0.
+    fn from_iter<I: IntoIterator<Item = ClusterBlock>>(iter: I) -> Self {
+        Cluster {
+            blocks: iter.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl BlockGroup {
+    /// Attach `data` to the Block as a `BlockAdditional` with the given `BlockAddID`,
+    /// e.g. HDR10+ dynamic metadata, creating the `BlockAdditions` if needed.
+    ///
+    /// Returns [`Error::DuplicateBlockAddId`] if the Block already has an additional with that ID,
+    /// leaving the BlockGroup unchanged.
+    pub fn add_block_additional(&mut self, id: u64, data: Vec<u8>) -> crate::Result<()> {
+        let additions = self
+            .block_additions
+            .get_or_insert_with(BlockAdditions::default);
+        if additions
+            .block_more
+            .iter()
+            .any(|more| *more.block_add_id == id)
+        {
+            return Err(Error::DuplicateBlockAddId(id));
+        }
+        additions.block_more.push(BlockMore {
+            block_additional: crate::leaf::BlockAdditional(data.into()),
+            block_add_id: crate::leaf::BlockAddId(id),
+            ..Default::default()
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leaf::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_validate_lacing() {
+        use crate::element::Strictness;
+        use crate::master::TrackEntry;
+
+        let tracks = Tracks {
+            track_entry: vec![TrackEntry {
+                track_number: TrackNumber(1),
+                flag_lacing: FlagLacing(0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        // Xiph lacing of 2 frames on track 1, then an unlaced block
+        let laced = SimpleBlock(Bytes::from_static(&[
+            0x81, 0x00, 0x00, 0x82, 0x01, 0x01, 1, 2,
+        ]));
+        let unlaced = SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x00, 0x80, 1]));
+        let strict = DecodeOptions::with_strictness(Strictness::Strict);
+
+        let mut cluster = Cluster {
+            blocks: vec![unlaced.into()],
+            ..Default::default()
+        };
+        cluster.validate_lacing(&tracks, &strict).unwrap();
+
+        cluster.push_block(laced);
+        assert!(matches!(
+            cluster.validate_lacing(&tracks, &strict),
+            Err(Error::UnexpectedLacing(1))
+        ));
+        cluster
+            .validate_lacing(&tracks, &DecodeOptions::default())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_shift_timestamp() {
+        let mut cluster = Cluster {
+            timestamp: Timestamp(1000),
+            blocks: vec![
+                SimpleBlock(Bytes::from_static(&[0x81, 0xFF, 0xF6, 0x80, 1])).into(),
+                BlockGroup {
+                    block: Block(Bytes::from_static(&[0x81, 0x00, 0x28, 0x00, 2])),
+                    ..Default::default()
+                }
+                .into(),
+            ],
+            ..Default::default()
+        };
+        let timestamps = |c: &Cluster| {
+            c.frames()
+                .map(|f| *f.unwrap().timestamp)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(timestamps(&cluster), vec![990, 1040]);
+
+        cluster.shift_timestamp(5000).unwrap();
+        assert_eq!(*cluster.timestamp, 6000);
+        assert_eq!(timestamps(&cluster), vec![5990, 6040]);
+
+        cluster.shift_timestamp(-6000).unwrap();
+        assert_eq!(timestamps(&cluster), vec![-10, 40]);
+
+        assert!(cluster.shift_timestamp(-1).is_err());
+        assert_eq!(*cluster.timestamp, 0);
+    }
+
+    #[test]
+    fn test_push_mixed_blocks() {
+        let mut cluster = Cluster::default();
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[
+            0x81, 0x00, 0x00, 0x80, 1,
+        ])));
+        cluster.push_block(BlockGroup {
+            block: Block(Bytes::from_static(&[0x82, 0x00, 0x00, 0x00, 2])),
+            ..Default::default()
+        });
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[
+            0x81, 0x00, 0x14, 0x80, 3,
+        ])));
+
+        assert!(matches!(cluster.blocks[1], ClusterBlock::Group(_)));
+        let tracks = cluster
+            .frames()
+            .map(|f| f.unwrap().track_number)
+            .collect::<Vec<_>>();
+        assert_eq!(tracks, vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn test_subtitle_cues() {
+        let mut cluster = Cluster {
+            timestamp: Timestamp(2000),
+            ..Default::default()
+        };
+        // track 3 at 2010, lasting 1500
+        cluster.push_block(BlockGroup {
+            block: Block(Bytes::from_static(b"\x83\x00\x0A\x00Hello")),
+            block_duration: Some(BlockDuration(1500)),
+            ..Default::default()
+        });
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[
+            0x81, 0x00, 0x00, 0x80, 0xFF,
+        ])));
+
+        let cues = cluster.subtitle_cues(3, 1_000_000).unwrap();
+        assert_eq!(cues, vec![(2_010_000_000, Some(1_500_000_000), "Hello")]);
+
+        // the frame of track 1 is not valid UTF-8
+        assert!(matches!(
+            cluster.subtitle_cues(1, 1_000_000),
+            Err(Error::InvalidUtf8(_))
+        ));
+    }
+
+    #[test]
+    fn test_timestamps_ns() {
+        let block = |track: u8, relative: i16| {
+            let mut body = vec![0x80 | track];
+            body.extend_from_slice(&relative.to_be_bytes());
+            body.extend_from_slice(&[0x80, 0xAA]);
+            SimpleBlock(Bytes::from(body))
+        };
+        let mut cluster = Cluster {
+            timestamp: Timestamp(1500),
+            ..Default::default()
+        };
+        cluster.push_block(block(1, 0));
+        cluster.push_block(block(2, -20));
+        cluster.push_block(BlockGroup {
+            block: crate::leaf::Block(block(1, 40).0),
+            ..Default::default()
+        });
+
+        // 100µs ticks
+        let scale = 100_000;
+        assert_eq!(cluster.timestamp_ns(scale).unwrap(), 150_000_000);
+        let times = cluster
+            .block_timestamps_ns(scale)
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            times,
+            vec![(1, 150_000_000), (2, 148_000_000), (1, 154_000_000)]
+        );
+
+        // a zero scale or an overflowing timestamp is an error
+        assert!(matches!(
+            cluster.timestamp_ns(0),
+            Err(Error::InvalidTimestampScale(0))
+        ));
+        assert!(matches!(
+            cluster.block_timestamps_ns(u64::MAX / 2).next(),
+            Some(Err(Error::TimestampOverflow { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_merge() {
+        let block = |track: u8, relative: i16| {
+            let mut body = vec![0x80 | track];
+            body.extend_from_slice(&relative.to_be_bytes());
+            body.extend_from_slice(&[0x80, 0xAA]);
+            SimpleBlock(Bytes::from(body))
+        };
+        let mut first = Cluster {
+            timestamp: Timestamp(1000),
+            position: Some(Position(12)),
+            ..Default::default()
+        };
+        first.push_block(block(1, 0));
+        first.push_block(block(2, 10));
+        let mut second = Cluster {
+            timestamp: Timestamp(1040),
+            ..Default::default()
+        };
+        second.push_block(block(1, -5));
+        second.push_block(BlockGroup {
+            block: crate::leaf::Block(block(1, 20).0),
+            reference_block: vec![ReferenceBlock(-40)],
+            ..Default::default()
+        });
+
+        let timestamps = |clusters: &[Cluster]| {
+            clusters
+                .iter()
+                .flat_map(Cluster::frames)
+                .map(|f| f.map(|f| (f.track_number, *f.timestamp)))
+                .collect::<crate::Result<Vec<_>>>()
+                .unwrap()
+        };
+        let clusters = [first, second];
+        let merged = Cluster::merge(&clusters).unwrap();
+        assert_eq!(*merged.timestamp, 1000);
+        assert_eq!(merged.position, None);
+        assert_eq!(merged.blocks.len(), 4);
+        assert_eq!(
+            timestamps(std::slice::from_ref(&merged)),
+            timestamps(&clusters)
+        );
+        let ClusterBlock::Group(group) = &merged.blocks[3] else {
+            panic!("expected a BlockGroup");
+        };
+        assert_eq!(*group.reference_block[0], -40);
+
+        let far = Cluster {
+            timestamp: Timestamp(1000 + i16::MAX as u64),
+            blocks: vec![block(1, 1).into()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            Cluster::merge(&[merged, far]),
+            Err(Error::RelativeTimestampOutOfRange { relative: 32768 })
+        ));
+        assert!(Cluster::merge(&[]).unwrap().blocks.is_empty());
+    }
+
+    #[test]
+    fn test_add_block_additional() {
+        let mut group = BlockGroup::default();
+        group.add_block_additional(4, vec![0xB5, 0x00]).unwrap();
+        group.add_block_additional(1, vec![0xAA]).unwrap();
+        assert!(matches!(
+            group.add_block_additional(4, vec![0xFF]),
+            Err(Error::DuplicateBlockAddId(4))
+        ));
+
+        let more = &group.block_additions.as_ref().unwrap().block_more;
+        assert_eq!(more.len(), 2);
+        assert_eq!(*more[0].block_add_id, 4);
+        assert_eq!(&more[0].block_additional[..], [0xB5, 0x00]);
+        assert_eq!(*more[1].block_add_id, 1);
+    }
+
+    #[test]
+    fn test_collect_blocks() {
+        let block = |n: u8| SimpleBlock(Bytes::from(vec![0x81, 0x00, n, 0x80, 0xAA]));
+        let mut cluster: Cluster = (0..3).map(|n| block(n).into()).collect();
+        assert_eq!(*cluster.timestamp, 0);
+        assert_eq!(cluster.blocks.len(), 3);
+
+        cluster.timestamp = Timestamp(100);
+        cluster.extend([
+            ClusterBlock::Group(BlockGroup {
+                block: crate::leaf::Block(block(3).0),
+                ..Default::default()
+            }),
+            block(4).into(),
+        ]);
+        let timestamps = cluster
+            .frames()
+            .map(|f| *f.unwrap().timestamp)
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps, vec![100, 101, 102, 103, 104]);
+        assert!(matches!(cluster.blocks[3], ClusterBlock::Group(_)));
+    }
+
+    #[test]
+    fn test_frame_count() {
+        let mut cluster = Cluster::default();
+        // keyframe
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[0x81, 0, 0, 0x80, 0xAA])));
+        // 3 frames laced with fixed-size lacing, not keyframes
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[
+            0x82, 0, 1, 0x04, 0x02, 1, 2, 3,
+        ])));
+        // keyframe group
+        cluster.push_block(BlockGroup {
+            block: crate::leaf::Block(Bytes::from_static(&[0x81, 0, 2, 0x00, 0xBB])),
+            ..Default::default()
+        });
+        // group referencing the keyframe
+        cluster.push_block(BlockGroup {
+            block: crate::leaf::Block(Bytes::from_static(&[0x81, 0, 3, 0x00, 0xCC])),
+            reference_block: vec![crate::leaf::ReferenceBlock(-1)],
+            ..Default::default()
+        });
+
+        assert_eq!(cluster.frame_count().unwrap(), 6);
+        assert_eq!(cluster.keyframe_count().unwrap(), 2);
+        let delaced: usize = cluster
+            .frames()
+            .map(|f| match f.unwrap().data {
+                FrameData::Single(_) => 1,
+                FrameData::Multiple(frames) => frames.len(),
+            })
+            .sum();
+        assert_eq!(delaced, 6);
+
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[0x81, 0])));
+        assert!(cluster.frame_count().is_err());
+    }
+
+    #[test]
+    fn test_header_info() {
+        let simple = SimpleBlock(Bytes::from_static(&[0x82, 0xFF, 0xF6, 0x86, 0x01, 1, 2]));
+        let (track, timestamp, flags) = simple.header_info().unwrap();
+        assert_eq!((track, timestamp), (2, -10));
+        assert!(flags.keyframe());
+        assert_eq!(flags.lacing(), Some(crate::Lacer::Ebml));
+
+        let group = BlockGroup {
+            block: crate::leaf::Block(Bytes::from_static(&[0x40, 0x81, 0x01, 0x00, 0x00, 0xAA])),
+            ..Default::default()
+        };
+        let (track, timestamp, flags) = group.header_info().unwrap();
+        assert_eq!((track, timestamp), (0x81, 256));
+        assert_eq!(flags.lacing(), None);
+
+        assert!(
+            SimpleBlock(Bytes::from_static(&[0x81, 0x00]))
+                .header_info()
+                .is_err()
+        );
+    }
+}
@@ -18,6 +18,39 @@ impl<const N: usize> Decode for [u8; N] {
     }
 }
 
+/// Decode exactly `n` items from the buffer, allocating for all of them upfront.
+///
+/// Useful when the count is stored ahead of the items, so the `Vec` never has to grow.
+pub(crate) fn decode_n<T: Decode>(buf: &mut dyn Buf, n: usize) -> Result<Vec<T>> {
+    let mut out = Vec::with_capacity(n);
+    for _ in 0..n {
+        out.push(T::decode(buf)?);
+    }
+    Ok(out)
+}
+
+/// Convert an 80-bit extended precision float (big-endian) to the nearest `f64`.
+pub(crate) fn f64_from_extended(bytes: [u8; 10]) -> f64 {
+    let sign = if bytes[0] & 0x80 != 0 { -1.0 } else { 1.0 };
+    let exponent = (u16::from_be_bytes([bytes[0], bytes[1]]) & 0x7FFF) as i32;
+    // the integer bit is explicit in the extended format
+    let mantissa = u64::from_be_bytes(bytes[2..].try_into().unwrap());
+    if exponent == 0x7FFF {
+        return if mantissa << 1 == 0 {
+            sign * f64::INFINITY
+        } else {
+            f64::NAN
+        };
+    }
+    if mantissa == 0 {
+        return sign * 0.0;
+    }
+    // value = mantissa * 2^(exponent - bias - 63), scaled in two steps to avoid
+    // overflowing the intermediate power of two
+    let scale = exponent - 16383 - 63;
+    sign * mantissa as f64 * 2f64.powi(scale / 2) * 2f64.powi(scale - scale / 2)
+}
+
 /// Encode an element to a buffer.
 pub trait Encode {
     /// Encode self to the buffer.
@@ -52,3 +85,23 @@ impl<T: Encode> Encode for Vec<T> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::VInt64;
+
+    #[test]
+    fn test_decode_n() {
+        let data = [0x81u8, 0x40, 0x02, 0x8A, 0xFF];
+        let buf = &mut &data[..];
+        let vints: Vec<VInt64> = decode_n(buf, 3).unwrap();
+        assert_eq!(vints, vec![VInt64::new(1), VInt64::new(2), VInt64::new(10)]);
+        assert_eq!(vints.capacity(), 3);
+        // the rest of the buffer is left alone
+        assert_eq!(*buf, [0xFF]);
+
+        assert!(decode_n::<VInt64>(&mut &data[..], 6).is_err());
+        assert!(decode_n::<[u8; 2]>(&mut &data[..], 0).unwrap().is_empty());
+    }
+}
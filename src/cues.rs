@@ -0,0 +1,227 @@
+//! Helpers for building Cues.
+
+use crate::Error;
+use crate::frame::{ClusterBlock, Frame};
+use crate::leaf::*;
+use crate::master::{Cluster, CuePoint, CueReference, CueTrackPositions, Cues};
+
+impl CueClusterPosition {
+    /// The absolute position of the Cluster in the file, given the position where the Segment
+    /// data starts, i.e. right after the Segment header.
+    pub fn to_absolute(&self, segment_data_position: u64) -> u64 {
+        segment_data_position + **self
+    }
+
+    /// The Segment Position of a Cluster at the absolute `position`,
+    /// see [`CueClusterPosition::to_absolute`].
+    ///
+    /// Returns [`crate::Error::InvalidSeekPosition`] if the position is before the Segment data.
+    pub fn from_absolute(position: u64, segment_data_position: u64) -> crate::Result<Self> {
+        position
+            .checked_sub(segment_data_position)
+            .map(CueClusterPosition)
+            .ok_or(crate::Error::InvalidSeekPosition(position))
+    }
+}
+
+/// Builder for a `Cues` element, indexing the blocks of Clusters as they are added.
+#[derive(Debug, Clone, Default)]
+pub struct CuesBuilder {
+    cue_point: Vec<CuePoint>,
+}
+
+impl CuesBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a cue point for every keyframe in the Cluster.
+    ///
+    /// `position` is the Segment Position of the Cluster, i.e. relative to the start of the Segment data.
+    pub fn add_cluster(&mut self, cluster: &Cluster, position: u64) -> crate::Result<()> {
+        for (index, block) in cluster.blocks.iter().enumerate() {
+            let frame = block.frame(*cluster.timestamp)?;
+            if frame.is_keyframe {
+                self.push_cue(block, &frame, position, index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a cue point for the block at `index` in the Cluster, which does not need to be a keyframe.
+    ///
+    /// If the block is a BlockGroup referencing other blocks, a `CueReference` is emitted for each
+    /// referenced timestamp, so a player seeking to this cue knows which blocks to decode first.
+    ///
+    /// Returns [`Error::BlockIndexOutOfRange`] if `index` is out of bounds of the Cluster blocks.
+    pub fn add_block(
+        &mut self,
+        cluster: &Cluster,
+        position: u64,
+        index: usize,
+    ) -> crate::Result<()> {
+        let block = cluster
+            .blocks
+            .get(index)
+            .ok_or(Error::BlockIndexOutOfRange {
+                index,
+                len: cluster.blocks.len(),
+            })?;
+        let frame = block.frame(*cluster.timestamp)?;
+        self.push_cue(block, &frame, position, index);
+        Ok(())
+    }
+
+    /// Push the cue point of `block`, the `index`-th of its Cluster, decoded as `frame`.
+    fn push_cue(&mut self, block: &ClusterBlock, frame: &Frame, position: u64, index: usize) {
+        let Ok(cue_time) = u64::try_from(*frame.timestamp) else {
+            log::warn!(
+                "Skipping cue for block with negative timestamp {}",
+                *frame.timestamp
+            );
+            return;
+        };
+
+        let cue_reference = match block {
+            ClusterBlock::Group(g) => g
+                .reference_block
+                .iter()
+                .filter_map(|r| u64::try_from(*frame.timestamp + **r).ok())
+                .map(|t| CueReference {
+                    cue_ref_time: CueRefTime(t),
+                    ..Default::default()
+                })
+                .collect(),
+            ClusterBlock::Simple(_) => vec![],
+        };
+
+        self.cue_point.push(CuePoint {
+            cue_time: CueTime(cue_time),
+            cue_track_positions: vec![CueTrackPositions {
+                cue_track: CueTrack(frame.track_number),
+                cue_cluster_position: CueClusterPosition(position),
+                cue_block_number: Some(CueBlockNumber(index as u64 + 1)),
+                cue_reference,
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+    }
+
+    /// Build the `Cues`, ordering cue points by time and merging the ones sharing the same time.
+    pub fn build(mut self) -> Cues {
+        self.cue_point.sort_by_key(|c| *c.cue_time);
+        let mut cue_point: Vec<CuePoint> = Vec::with_capacity(self.cue_point.len());
+        for point in self.cue_point {
+            match cue_point.last_mut() {
+                Some(last) if last.cue_time == point.cue_time => {
+                    last.cue_track_positions.extend(point.cue_track_positions)
+                }
+                _ => cue_point.push(point),
+            }
+        }
+        Cues {
+            cue_point,
+            ..Default::default()
+        }
+    }
+}
+
+impl Cues {
+    /// Iterate over the CuePoints, see also `&Cues` which is iterable.
+    pub fn iter(&self) -> std::slice::Iter<'_, CuePoint> {
+        self.cue_point.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Cues {
+    type Item = &'a CuePoint;
+    type IntoIter = std::slice::Iter<'a, CuePoint>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.cue_point.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::master::BlockGroup;
+    use bytes::Bytes;
+
+    fn cluster() -> Cluster {
+        Cluster {
+            timestamp: Timestamp(1000),
+            blocks: vec![
+                // keyframe, track 1, relative timestamp 0
+                SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x00, 0x80, 1, 2, 3])).into(),
+                // keyframe, track 2, relative timestamp 0
+                SimpleBlock(Bytes::from_static(&[0x82, 0x00, 0x00, 0x80, 4])).into(),
+                // non-keyframe, track 1, relative timestamp 40, referencing the first block
+                BlockGroup {
+                    block: Block(Bytes::from_static(&[0x81, 0x00, 0x28, 0x00, 5, 6])),
+                    reference_block: vec![ReferenceBlock(-40)],
+                    ..Default::default()
+                }
+                .into(),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_keyframe_cues() {
+        let mut builder = CuesBuilder::new();
+        builder.add_cluster(&cluster(), 4096).unwrap();
+        let cues = builder.build();
+        assert_eq!(cues.cue_point.len(), 1);
+        let point = &cues.cue_point[0];
+        assert_eq!(*point.cue_time, 1000);
+        assert_eq!(point.cue_track_positions.len(), 2);
+        assert_eq!(*point.cue_track_positions[0].cue_track, 1);
+        assert_eq!(*point.cue_track_positions[1].cue_track, 2);
+        assert!(
+            point
+                .cue_track_positions
+                .iter()
+                .all(|p| *p.cue_cluster_position == 4096 && p.cue_reference.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_cue_reference() {
+        let mut builder = CuesBuilder::new();
+        builder.add_block(&cluster(), 4096, 2).unwrap();
+        let cues = builder.build();
+        assert_eq!(cues.cue_point.len(), 1);
+        let point = &cues.cue_point[0];
+        assert_eq!(*point.cue_time, 1040);
+        let positions = &point.cue_track_positions[0];
+        assert_eq!(positions.cue_block_number, Some(CueBlockNumber(3)));
+        assert_eq!(positions.cue_reference.len(), 1);
+        assert_eq!(*positions.cue_reference[0].cue_ref_time, 1000);
+
+        // an out-of-range index is an error, not a panic
+        assert!(matches!(
+            CuesBuilder::new().add_block(&cluster(), 4096, 3),
+            Err(Error::BlockIndexOutOfRange { index: 3, len: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_cluster_position_absolute() {
+        // Segment header of 12 bytes after a 40-byte EBML header
+        let segment_data_position = 52;
+        let position = CueClusterPosition(4096);
+        assert_eq!(position.to_absolute(segment_data_position), 4148);
+        assert_eq!(
+            CueClusterPosition::from_absolute(4148, segment_data_position).unwrap(),
+            position
+        );
+        assert!(matches!(
+            CueClusterPosition::from_absolute(40, segment_data_position),
+            Err(crate::Error::InvalidSeekPosition(40))
+        ));
+        assert_eq!(SeekPosition(10).to_absolute(segment_data_position), 62);
+    }
+}
@@ -0,0 +1,83 @@
+//! Helpers for working with the EBML header.
+
+use crate::Error;
+use crate::element::Element;
+use crate::leaf::{DocTypeReadVersion, EbmlReadVersion};
+use crate::master::Ebml;
+
+/// The highest `EBMLReadVersion` this crate reads.
+const EBML_READ_VERSION: u64 = 1;
+
+/// The highest `DocTypeReadVersion` this crate reads, for the doc types it knows.
+fn doc_type_read_version(doc_type: &str) -> Option<u64> {
+    match doc_type {
+        "matroska" | "webm" => Some(4),
+        _ => None,
+    }
+}
+
+impl Ebml {
+    /// Check that a file with this header can be read correctly by this crate.
+    ///
+    /// Returns [`Error::UnsupportedVersion`] if the `EBMLReadVersion` is above 1, or the
+    /// `DocTypeReadVersion` of a `matroska` or `webm` file above 4. Other doc types are not checked.
+    /// Missing versions take their default of 1.
+    pub fn is_readable(&self) -> crate::Result<()> {
+        let ebml_read_version = self.ebml_read_version.map_or(1, |v| *v);
+        if ebml_read_version > EBML_READ_VERSION {
+            return Err(Error::UnsupportedVersion {
+                element: EbmlReadVersion::ID,
+                version: ebml_read_version,
+                supported: EBML_READ_VERSION,
+            });
+        }
+
+        let doc_type = self.doc_type.as_ref().map_or("matroska", |d| d.0.as_str());
+        let read_version = self.doc_type_read_version.map_or(1, |v| *v);
+        match doc_type_read_version(doc_type) {
+            Some(supported) if read_version > supported => Err(Error::UnsupportedVersion {
+                element: DocTypeReadVersion::ID,
+                version: read_version,
+                supported,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leaf::*;
+
+    #[test]
+    fn test_is_readable() {
+        let mut ebml = Ebml {
+            doc_type: Some(DocType("webm".to_string())),
+            doc_type_version: Some(DocTypeVersion(4)),
+            doc_type_read_version: Some(DocTypeReadVersion(2)),
+            ..Default::default()
+        };
+        assert!(ebml.is_readable().is_ok());
+
+        ebml.doc_type_read_version = Some(DocTypeReadVersion(1000));
+        assert!(matches!(
+            ebml.is_readable(),
+            Err(Error::UnsupportedVersion {
+                element,
+                version: 1000,
+                supported: 4
+            }) if element == DocTypeReadVersion::ID
+        ));
+
+        // unknown doc types are left to the caller
+        ebml.doc_type = Some(DocType("mka3d".to_string()));
+        assert!(ebml.is_readable().is_ok());
+
+        ebml.ebml_read_version = Some(EbmlReadVersion(2));
+        assert!(matches!(
+            ebml.is_readable(),
+            Err(Error::UnsupportedVersion { element, .. }) if element == EbmlReadVersion::ID
+        ));
+    }
+}
@@ -16,8 +16,206 @@ pub trait Element: Sized {
     /// Decode the body of the element from a buffer.
     fn decode_body(buf: &mut dyn Buf) -> crate::Result<Self>;
 
+    /// Decode the body of the element from a buffer with the given options.
+    ///
+    /// Master elements pass the options down to their children. By default the options are ignored.
+    fn decode_body_with(buf: &mut dyn Buf, options: &DecodeOptions) -> crate::Result<Self> {
+        let _ = options;
+        Self::decode_body(buf)
+    }
+
     /// Encode the body of the element to a buffer.
     fn encode_body<B: BufMut>(&self, buf: &mut B) -> crate::Result<()>;
+
+    /// Encode the body of the element into a `Vec`, in place.
+    ///
+    /// Master elements encode their children with [`Element::encode_into`]. By default this is
+    /// [`Element::encode_body`].
+    fn encode_body_into(&self, buf: &mut Vec<u8>) -> crate::Result<()> {
+        self.encode_body(buf)
+    }
+
+    /// Encode the element into a `Vec`, decoding to the same element as [`WriteTo::write_to`].
+    ///
+    /// Instead of encoding the body of a master element into an intermediate buffer to learn its
+    /// size, a size slot of 8 bytes is reserved in the output, the body is encoded in place after
+    /// it, and the size is back-patched, as in [`crate::ClusterWriter`]. This avoids an allocation
+    /// and a copy per nested element, at the cost of non-minimal sizes for master elements.
+    /// Other elements are encoded as with `Encode::encode`.
+    fn encode_into(&self, buf: &mut Vec<u8>) -> crate::Result<()> {
+        self.encode(buf)
+    }
+
+    /// Decode a run of elements of this type, each with its header, until `buf` is empty,
+    /// e.g. the `Seek` elements of a SeekHead body.
+    ///
+    /// Returns [`Error::UnexpectedElement`] when an element of another type is found.
+    fn decode_all(buf: &mut &[u8]) -> crate::Result<Vec<Self>> {
+        let mut elements = vec![];
+        while !buf.is_empty() {
+            let header = Header::decode(&mut &buf[..])?;
+            if header.id != Self::ID {
+                return Err(Error::UnexpectedElement {
+                    expected: Self::ID,
+                    found: header.id,
+                });
+            }
+            elements.push(Self::decode(buf)?);
+        }
+        Ok(elements)
+    }
+
+    /// Remove the CRC-32 and Void elements from this element and its children, recursively.
+    ///
+    /// Leaf elements have nothing to remove.
+    fn strip_padding(&mut self) {}
+}
+
+/// A master element, made of child elements, with an optional CRC-32 as first child.
+pub trait MasterElement: Element + Clone {
+    /// The CRC-32 child of the element, if any.
+    fn crc32(&self) -> Option<&crate::supplement::Crc32>;
+    /// Set or remove the CRC-32 child of the element.
+    fn set_crc32(&mut self, crc32: Option<crate::supplement::Crc32>);
+}
+
+/// Width in bytes reserved for a size that is back-patched once the element is complete, by
+/// [`Element::encode_into`] and the streaming writers.
+pub(crate) const RESERVED_SIZE_WIDTH: usize = 8;
+
+/// Encode the master `element` into a `Vec`, back-patching its size, see [`Element::encode_into`].
+pub(crate) fn encode_master_into<T: Element>(element: &T, buf: &mut Vec<u8>) -> crate::Result<()> {
+    T::ID.encode(buf)?;
+    let size_position = buf.len();
+    buf.extend_from_slice(&[0; RESERVED_SIZE_WIDTH]);
+    let body_position = buf.len();
+    element.encode_body_into(buf)?;
+
+    let size = VInt64::new((buf.len() - body_position) as u64);
+    size.encode_with_width(
+        RESERVED_SIZE_WIDTH,
+        &mut &mut buf[size_position..body_position],
+    )?;
+    Ok(())
+}
+
+/// How strictly elements are validated while decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strictness {
+    /// Accept malformed data whenever a sensible value can still be recovered.
+    Lenient,
+    /// Reject malformed structure, without verifying integrity checks. This is the default.
+    #[default]
+    Normal,
+    /// Additionally reject data failing integrity checks, such as a CRC-32 mismatch.
+    Strict,
+}
+
+/// Options controlling how elements are decoded.
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    /// How strictly elements are validated.
+    pub strictness: Strictness,
+    /// Keep unknown child elements of master elements as [`RawElement`](crate::prelude::RawElement)s,
+    /// instead of skipping them, so they are written back on encode.
+    pub preserve_unknown: bool,
+    /// Record the width of the encoded size and the position of each child of master elements in
+    /// their [`SizeWidths`](crate::prelude::SizeWidths), so non-minimal sizes and the order of the
+    /// children are written back as is.
+    pub preserve_size_width: bool,
+    /// Maximum number of children, Void elements included, in a single master element.
+    ///
+    /// Going over it fails with [`Error::DecodeBudgetExceeded`], bounding the work spent on
+    /// adversarial files made of many tiny elements. Defaults to [`DecodeOptions::MAX_CHILDREN`].
+    pub max_children: usize,
+    /// Maximum nesting depth of master elements, the element being decoded included.
+    ///
+    /// Going over it fails with [`Error::MaxDepthExceeded`], bounding the recursion on
+    /// adversarial files made of deeply nested elements, such as ChapterAtoms or SimpleTags.
+    /// Defaults to [`DecodeOptions::MAX_DEPTH`].
+    pub max_depth: usize,
+    /// Called with the header of each unknown child element that is skipped, instead of logging
+    /// a warning for it, e.g. to count the junk elements of a damaged file.
+    ///
+    /// Unknown elements kept with `preserve_unknown` are not reported.
+    pub on_unknown: Option<fn(&Header)>,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            strictness: Strictness::default(),
+            preserve_unknown: false,
+            preserve_size_width: false,
+            max_children: Self::MAX_CHILDREN,
+            max_depth: Self::MAX_DEPTH,
+            on_unknown: None,
+        }
+    }
+}
+
+impl DecodeOptions {
+    /// Default maximum number of children in a master element, far above what real files use.
+    pub const MAX_CHILDREN: usize = 1 << 20;
+
+    /// Default maximum nesting depth of master elements, far above what real files use.
+    pub const MAX_DEPTH: usize = 64;
+
+    /// Options with the given strictness.
+    pub fn with_strictness(strictness: Strictness) -> Self {
+        Self {
+            strictness,
+            ..Default::default()
+        }
+    }
+
+    /// These options, also keeping unknown child elements.
+    pub fn preserving_unknown(self) -> Self {
+        Self {
+            preserve_unknown: true,
+            ..self
+        }
+    }
+
+    /// These options, also keeping the width of the encoded sizes.
+    pub fn preserving_size_width(self) -> Self {
+        Self {
+            preserve_size_width: true,
+            ..self
+        }
+    }
+
+    /// Whether integrity checks, such as CRC-32, are verified.
+    pub fn is_strict(&self) -> bool {
+        self.strictness == Strictness::Strict
+    }
+
+    /// Whether recoverable malformed data is accepted.
+    pub fn is_lenient(&self) -> bool {
+        self.strictness == Strictness::Lenient
+    }
+
+    /// The options to decode the children of the master element `id`, one level deeper.
+    pub(crate) fn nested(&self, id: VInt64) -> crate::Result<Self> {
+        let max_depth = self
+            .max_depth
+            .checked_sub(1)
+            .ok_or(Error::MaxDepthExceeded(id))?;
+        Ok(Self {
+            max_depth,
+            ..self.clone()
+        })
+    }
+}
+
+/// Decode the body of an element, mapping buffer errors to errors about the element.
+fn decode_element_body<T: Element>(buf: &mut dyn Buf, options: &DecodeOptions) -> crate::Result<T> {
+    match T::decode_body_with(buf, options) {
+        Ok(e) => Ok(e),
+        Err(Error::TryGetError(_)) => Err(Error::OverDecode(T::ID)),
+        Err(Error::ShortRead) => Err(Error::UnderDecode(T::ID)),
+        Err(e) => Err(e),
+    }
 }
 
 impl<T: Element> Decode for T {
@@ -28,12 +226,7 @@ impl<T: Element> Decode for T {
             return Err(Error::try_get_error(body_size, buf.remaining()));
         }
         let mut body = buf.take(body_size);
-        let element = match T::decode_body(&mut body) {
-            Ok(e) => e,
-            Err(Error::TryGetError(_)) => return Err(Error::OverDecode(Self::ID)),
-            Err(Error::ShortRead) => return Err(Error::UnderDecode(Self::ID)),
-            Err(e) => return Err(e),
-        };
+        let element = decode_element_body::<T>(&mut body, &DecodeOptions::default())?;
 
         if body.has_remaining() {
             return Err(Error::UnderDecode(Self::ID));
@@ -47,10 +240,7 @@ impl<T: Element> Encode for T {
     fn encode<B: BufMut>(&self, buf: &mut B) -> crate::Result<()> {
         let mut body_buf = Vec::new();
         self.encode_body(&mut body_buf)?;
-        let header = Header {
-            id: T::ID,
-            size: VInt64::new(body_buf.len() as u64),
-        };
+        let header = Header::new(T::ID, VInt64::new(body_buf.len() as u64));
         header.encode(buf)?;
         buf.put_slice(&body_buf);
         Ok(())
@@ -59,15 +249,18 @@ impl<T: Element> Encode for T {
 
 impl<T: Element> ReadFrom for T {
     fn read_from<R: std::io::Read + ?Sized>(r: &mut R) -> crate::Result<Self> {
+        T::read_from_with(r, &DecodeOptions::default())
+    }
+}
+
+impl<T: Element> ReadFromWith for T {
+    fn read_from_with<R: std::io::Read + ?Sized>(
+        r: &mut R,
+        options: &DecodeOptions,
+    ) -> crate::Result<Self> {
         let header = Header::read_from(r)?;
         let body = header.read_body(r)?;
-        let element = match T::decode_body(&mut &body[..]) {
-            Ok(e) => e,
-            Err(Error::TryGetError(_)) => return Err(Error::OverDecode(Self::ID)),
-            Err(Error::ShortRead) => return Err(Error::UnderDecode(Self::ID)),
-            Err(e) => return Err(e),
-        };
-        Ok(element)
+        decode_element_body::<T>(&mut &body[..], options)
     }
 }
 
@@ -77,14 +270,24 @@ impl<T: Element> crate::io::tokio_impl::AsyncReadFrom for T {
     async fn async_read_from<R: tokio::io::AsyncRead + Unpin + ?Sized>(
         r: &mut R,
     ) -> crate::Result<Self> {
+        <T as crate::io::tokio_impl::AsyncReadFromWith>::async_read_from_with(
+            r,
+            &DecodeOptions::default(),
+        )
+        .await
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+impl<T: Element> crate::io::tokio_impl::AsyncReadFromWith for T {
+    async fn async_read_from_with<R: tokio::io::AsyncRead + Unpin + ?Sized>(
+        r: &mut R,
+        options: &DecodeOptions,
+    ) -> crate::Result<Self> {
+        use crate::io::tokio_impl::AsyncReadFrom;
         let header = Header::async_read_from(r).await?;
         let body = header.read_body_tokio(r).await?;
-        let element = match T::decode_body(&mut &body[..]) {
-            Ok(e) => e,
-            Err(Error::TryGetError(_)) => return Err(Error::OverDecode(Self::ID)),
-            Err(Error::ShortRead) => return Err(Error::UnderDecode(Self::ID)),
-            Err(e) => return Err(e),
-        };
-        Ok(element)
+        decode_element_body::<T>(&mut &body[..], options)
     }
 }
@@ -12,6 +12,28 @@ pub enum Error {
     #[error("Invalid variable-length integer encoding, 8 leading zeros found...")]
     InvalidVInt,
 
+    /// A string is not a valid encoded variable-length integer.
+    #[error("Invalid variable-length integer string: {0:?}")]
+    InvalidVIntString(String),
+
+    /// A variable-length integer does not fit in the requested encoding width.
+    #[error("Value {value} does not fit in a {width}-byte variable-length integer")]
+    VIntOverflow {
+        /// The value to encode.
+        value: u64,
+        /// The requested width in bytes.
+        width: usize,
+    },
+
+    /// A variable-length integer is encoded on more bytes than needed, rejected by [`VInt64::read_minimal`].
+    #[error("Value {value} is encoded on {width} bytes, more than needed")]
+    NonMinimalVInt {
+        /// The decoded value.
+        value: u64,
+        /// The width of the encoding in bytes.
+        width: usize,
+    },
+
     /// Attempted to read past the end of the buffer.
     #[error("Attempted to read past the end of the buffer")]
     TryGetError(#[from] bytes::TryGetError),
@@ -32,6 +54,15 @@ pub enum Error {
     #[error("Missing element, ID: {0}")]
     MissingElement(VInt64),
 
+    /// An element of another type was found where a run of elements of one type was expected.
+    #[error("Unexpected element {found}, expected {expected}")]
+    UnexpectedElement {
+        /// ID of the expected element.
+        expected: VInt64,
+        /// ID of the element found.
+        found: VInt64,
+    },
+
     /// Duplicate element in a master element.
     #[error("Duplicate element {id} in master element {parent}")]
     DuplicateElement {
@@ -41,16 +72,399 @@ pub enum Error {
         parent: VInt64,
     },
 
+    /// The CRC-32 of a master element does not match its body, only checked in strict mode.
+    #[error("CRC-32 mismatch in element {id}: expected {expected:#010x}, computed {actual:#010x}")]
+    Crc32Mismatch {
+        /// The master element ID.
+        id: VInt64,
+        /// The CRC-32 stored in the element.
+        expected: u32,
+        /// The CRC-32 computed from the element body.
+        actual: u32,
+    },
+
+    /// A required element without default value has an empty body, only checked in strict mode.
+    #[error("Empty body for required element, ID: {0}")]
+    EmptyRequiredElement(VInt64),
+
     /// Element body size is unknown.
     #[error("Element body size is unknown, ID: {0}")]
     ElementBodySizeUnknown(VInt64),
 
+    /// Frame data is not valid UTF-8 text.
+    #[error("Invalid UTF-8 text: {0}")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+
     /// Malformed lacing data.
     #[error("Malformed lacing data")]
     MalformedLacingData,
+
+    /// A block is laced while its track has `FlagLacing` set to 0.
+    #[error("Laced block on track {0}, which does not allow lacing")]
+    UnexpectedLacing(u64),
+
+    /// Shifting a timestamp moved it out of the representable range.
+    #[error("Timestamp {timestamp} shifted by {delta} is out of range")]
+    TimestampOutOfRange {
+        /// The timestamp before shifting.
+        timestamp: u64,
+        /// The requested shift.
+        delta: i64,
+    },
+
+    /// A timestamp converted to nanoseconds does not fit in an `i64`.
+    #[error("Timestamp overflows in nanoseconds with TimestampScale {timestamp_scale}")]
+    TimestampOverflow {
+        /// The `TimestampScale` used for the conversion.
+        timestamp_scale: u64,
+    },
+
+    /// Segments with different `TimestampScale`s are combined.
+    #[error("TimestampScale {found} differs from {expected}")]
+    TimestampScaleMismatch {
+        /// The `TimestampScale` of the Segment combined into.
+        expected: u64,
+        /// The `TimestampScale` of the other Segment.
+        found: u64,
+    },
+
+    /// A `TimestampScale` of 0, or too large to be used in timestamp conversions.
+    #[error("Invalid TimestampScale: {0}")]
+    InvalidTimestampScale(u64),
+
+    /// A block timestamp, relative to its Cluster, does not fit in the 16 bits of a Block header.
+    #[error("Relative block timestamp {relative} does not fit in an i16")]
+    RelativeTimestampOutOfRange {
+        /// The relative timestamp, in ticks.
+        relative: i64,
+    },
+
+    /// A signed integer is encoded on more bytes than needed, rejected in strict mode.
+    #[error("Non-minimal signed integer encoding in element {0}")]
+    NonMinimalInteger(VInt64),
+
+    /// A master element has more children than allowed by [`DecodeOptions::max_children`](crate::prelude::DecodeOptions::max_children).
+    #[error("Decode budget exceeded in element {0}")]
+    DecodeBudgetExceeded(VInt64),
+
+    /// Master elements are nested deeper than allowed by [`DecodeOptions::max_depth`](crate::prelude::DecodeOptions::max_depth).
+    #[error("Maximum nesting depth exceeded in element {0}")]
+    MaxDepthExceeded(VInt64),
+
+    /// The frames of a track use a `ContentEncoding` this crate cannot undo, such as bzlib, or
+    /// zlib and encryption without their features enabled.
+    #[error("Unsupported ContentEncoding on track {0}")]
+    UnsupportedContentEncoding(u64),
+
+    /// The frames of a track are encrypted, but no key was given to decrypt them.
+    #[error("Missing decryption key for track {0}")]
+    MissingDecryptionKey(u64),
+
+    /// An encrypted frame of a track is too short for its header, or has invalid partitions.
+    #[error("Invalid encrypted frame on track {0}")]
+    InvalidEncryptedFrame(u64),
+
+    /// The `Position` of a Cluster differs from the offset it was found at, relative to the Segment data.
+    #[error("Cluster declares Position {declared}, but was found at {actual}")]
+    PositionMismatch {
+        /// The position declared in the Cluster.
+        declared: u64,
+        /// The actual position of the Cluster.
+        actual: u64,
+    },
+
+    /// A SimpleTag has both a `TagString` and a `TagBinary`, which are mutually exclusive.
+    #[error("SimpleTag {0:?} has both a TagString and a TagBinary")]
+    ConflictingTagValue(String),
+
+    /// An absolute position converted to a Segment Position, e.g. for a SeekHead, is before the
+    /// start of the Segment data.
+    #[error("Seek position {0} is before the Segment data")]
+    InvalidSeekPosition(u64),
+
+    /// A block references a track number that is not present in the Tracks element.
+    #[error("Unknown track referenced by a block: {0}")]
+    UnknownTrackReference(u64),
+
+    /// A track referenced by UID is not present in the Tracks element.
+    #[error("Track not found, UID: {0}")]
+    TrackNotFound(u64),
+
+    /// A block index is past the end of the blocks of a Cluster.
+    #[error("Block index {index} is out of range, the Cluster has {len} blocks")]
+    BlockIndexOutOfRange {
+        /// The requested index.
+        index: usize,
+        /// Number of blocks in the Cluster.
+        len: usize,
+    },
+
+    /// The EBML header requires a newer reader than this crate, see [`Ebml::is_readable`](crate::prelude::Ebml::is_readable).
+    #[error("Unsupported version {version} in {element}, up to {supported} is supported")]
+    UnsupportedVersion {
+        /// The ID of the read version element, `EBMLReadVersion` or `DocTypeReadVersion`.
+        element: VInt64,
+        /// The version required to read the file.
+        version: u64,
+        /// The highest version this crate reads.
+        supported: u64,
+    },
+
+    /// The `PixelCrop*` elements of a video remove the whole picture in one direction.
+    #[error("Crop of {crop} pixels does not fit in {size} pixels")]
+    InvalidCrop {
+        /// The sum of the crops on both sides.
+        crop: u64,
+        /// The pixel width or height being cropped.
+        size: u64,
+    },
+
+    /// A `MasteringMetadata` has some, but not all, of its chromaticity and luminance values.
+    #[error("MasteringMetadata has only {present} of its 10 values")]
+    IncompleteMasteringMetadata {
+        /// The number of values present.
+        present: usize,
+    },
+
+    /// Two `ContentEncoding`s of a track share the same `ContentEncodingOrder`.
+    #[error("Duplicate ContentEncodingOrder: {0}")]
+    DuplicateEncodingOrder(u64),
+
+    /// Two `BlockMore`s of a `BlockAdditions` share the same `BlockAddID`.
+    #[error("Duplicate BlockAddID: {0}")]
+    DuplicateBlockAddId(u64),
+
+    /// The `Video` and `Audio` settings of a track do not match its `TrackType`,
+    /// e.g. a video track without `Video` settings.
+    #[error("Video/Audio settings of track {track} do not match its TrackType {track_type}")]
+    TrackTypeMismatch {
+        /// The `TrackNumber` of the track.
+        track: u64,
+        /// The `TrackType` of the track.
+        track_type: u64,
+    },
+
+    /// A `V_UNCOMPRESSED` track lacks a 4-byte `UncompressedFourCC`.
+    #[error("Missing UncompressedFourCC for V_UNCOMPRESSED track {0}")]
+    MissingFourcc(u64),
+
+    /// A chapter breaks an invariant of the Chapters element, e.g. a duplicate `ChapterUID`.
+    #[error("Invalid chapter: {detail}")]
+    InvalidChapter {
+        /// Description of the broken invariant.
+        detail: String,
+    },
+
+    /// The linking elements of a `Info` contradict each other, e.g. a `PrevUUID` equal to the `SegmentUUID`.
+    #[error("Invalid segment linking: {detail}")]
+    InvalidLinking {
+        /// Description of the broken invariant.
+        detail: String,
+    },
+
+    /// The Segment uses an element that is not allowed in a WebM file.
+    #[error("Not allowed in WebM: {detail}")]
+    InvalidWebm {
+        /// Description of the offending element.
+        detail: String,
+    },
+
+    /// Decoding a child of a master element failed, the wrapped error says why.
+    ///
+    /// Errors of nested elements are wrapped once per level, giving the chain of parents.
+    #[error("in element {parent}: {source}")]
+    InElement {
+        /// The master element ID.
+        parent: VInt64,
+        /// The error of the child element.
+        source: Box<Error>,
+    },
+
+    /// An attachment referenced by UID is not present in the Attachments element.
+    #[error("Attachment not found, UID: {0}")]
+    AttachmentNotFound(u64),
+}
+
+/// Stable discriminant of an [`Error`], for matching without depending on variant fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// See [`Error::Io`].
+    Io,
+    /// See [`Error::InvalidVInt`].
+    InvalidVInt,
+    /// See [`Error::InvalidVIntString`].
+    InvalidVIntString,
+    /// See [`Error::VIntOverflow`].
+    VIntOverflow,
+    /// See [`Error::NonMinimalVInt`].
+    NonMinimalVInt,
+    /// See [`Error::TryGetError`].
+    TryGetError,
+    /// See [`Error::OverDecode`].
+    OverDecode,
+    /// See [`Error::ShortRead`].
+    ShortRead,
+    /// See [`Error::UnderDecode`].
+    UnderDecode,
+    /// See [`Error::MissingElement`].
+    MissingElement,
+    /// See [`Error::UnexpectedElement`].
+    UnexpectedElement,
+    /// See [`Error::DuplicateElement`].
+    DuplicateElement,
+    /// See [`Error::Crc32Mismatch`].
+    Crc32Mismatch,
+    /// See [`Error::EmptyRequiredElement`].
+    EmptyRequiredElement,
+    /// See [`Error::ElementBodySizeUnknown`].
+    ElementBodySizeUnknown,
+    /// See [`Error::InvalidUtf8`].
+    InvalidUtf8,
+    /// See [`Error::MalformedLacingData`].
+    MalformedLacingData,
+    /// See [`Error::UnexpectedLacing`].
+    UnexpectedLacing,
+    /// See [`Error::TimestampOutOfRange`].
+    TimestampOutOfRange,
+    /// See [`Error::TimestampOverflow`].
+    TimestampOverflow,
+    /// See [`Error::TimestampScaleMismatch`].
+    TimestampScaleMismatch,
+    /// See [`Error::InvalidTimestampScale`].
+    InvalidTimestampScale,
+    /// See [`Error::RelativeTimestampOutOfRange`].
+    RelativeTimestampOutOfRange,
+    /// See [`Error::NonMinimalInteger`].
+    NonMinimalInteger,
+    /// See [`Error::DecodeBudgetExceeded`].
+    DecodeBudgetExceeded,
+    /// See [`Error::MaxDepthExceeded`].
+    MaxDepthExceeded,
+    /// See [`Error::UnsupportedContentEncoding`].
+    UnsupportedContentEncoding,
+    /// See [`Error::MissingDecryptionKey`].
+    MissingDecryptionKey,
+    /// See [`Error::InvalidEncryptedFrame`].
+    InvalidEncryptedFrame,
+    /// See [`Error::PositionMismatch`].
+    PositionMismatch,
+    /// See [`Error::ConflictingTagValue`].
+    ConflictingTagValue,
+    /// See [`Error::InvalidSeekPosition`].
+    InvalidSeekPosition,
+    /// See [`Error::UnknownTrackReference`].
+    UnknownTrackReference,
+    /// See [`Error::TrackNotFound`].
+    TrackNotFound,
+    /// See [`Error::BlockIndexOutOfRange`].
+    BlockIndexOutOfRange,
+    /// See [`Error::UnsupportedVersion`].
+    UnsupportedVersion,
+    /// See [`Error::InvalidCrop`].
+    InvalidCrop,
+    /// See [`Error::IncompleteMasteringMetadata`].
+    IncompleteMasteringMetadata,
+    /// See [`Error::DuplicateEncodingOrder`].
+    DuplicateEncodingOrder,
+    /// See [`Error::DuplicateBlockAddId`].
+    DuplicateBlockAddId,
+    /// See [`Error::TrackTypeMismatch`].
+    TrackTypeMismatch,
+    /// See [`Error::MissingFourcc`].
+    MissingFourcc,
+    /// See [`Error::InvalidChapter`].
+    InvalidChapter,
+    /// See [`Error::InvalidLinking`].
+    InvalidLinking,
+    /// See [`Error::InvalidWebm`].
+    InvalidWebm,
+    /// See [`Error::AttachmentNotFound`].
+    AttachmentNotFound,
 }
 
 impl Error {
+    /// Stable code of this error, for programmatic matching.
+    ///
+    /// For [`Error::InElement`], this is the code of the wrapped error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::InElement { source, .. } => source.code(),
+            Error::Io(_) => ErrorCode::Io,
+            Error::InvalidVInt => ErrorCode::InvalidVInt,
+            Error::InvalidVIntString(_) => ErrorCode::InvalidVIntString,
+            Error::VIntOverflow { .. } => ErrorCode::VIntOverflow,
+            Error::NonMinimalVInt { .. } => ErrorCode::NonMinimalVInt,
+            Error::TryGetError(_) => ErrorCode::TryGetError,
+            Error::OverDecode(_) => ErrorCode::OverDecode,
+            Error::ShortRead => ErrorCode::ShortRead,
+            Error::UnderDecode(_) => ErrorCode::UnderDecode,
+            Error::MissingElement(_) => ErrorCode::MissingElement,
+            Error::UnexpectedElement { .. } => ErrorCode::UnexpectedElement,
+            Error::DuplicateElement { .. } => ErrorCode::DuplicateElement,
+            Error::Crc32Mismatch { .. } => ErrorCode::Crc32Mismatch,
+            Error::EmptyRequiredElement(_) => ErrorCode::EmptyRequiredElement,
+            Error::ElementBodySizeUnknown(_) => ErrorCode::ElementBodySizeUnknown,
+            Error::InvalidUtf8(_) => ErrorCode::InvalidUtf8,
+            Error::MalformedLacingData => ErrorCode::MalformedLacingData,
+            Error::UnexpectedLacing(_) => ErrorCode::UnexpectedLacing,
+            Error::TimestampOutOfRange { .. } => ErrorCode::TimestampOutOfRange,
+            Error::TimestampOverflow { .. } => ErrorCode::TimestampOverflow,
+            Error::TimestampScaleMismatch { .. } => ErrorCode::TimestampScaleMismatch,
+            Error::InvalidTimestampScale(_) => ErrorCode::InvalidTimestampScale,
+            Error::RelativeTimestampOutOfRange { .. } => ErrorCode::RelativeTimestampOutOfRange,
+            Error::NonMinimalInteger(_) => ErrorCode::NonMinimalInteger,
+            Error::DecodeBudgetExceeded(_) => ErrorCode::DecodeBudgetExceeded,
+            Error::MaxDepthExceeded(_) => ErrorCode::MaxDepthExceeded,
+            Error::UnsupportedContentEncoding(_) => ErrorCode::UnsupportedContentEncoding,
+            Error::MissingDecryptionKey(_) => ErrorCode::MissingDecryptionKey,
+            Error::InvalidEncryptedFrame(_) => ErrorCode::InvalidEncryptedFrame,
+            Error::PositionMismatch { .. } => ErrorCode::PositionMismatch,
+            Error::ConflictingTagValue(_) => ErrorCode::ConflictingTagValue,
+            Error::InvalidSeekPosition(_) => ErrorCode::InvalidSeekPosition,
+            Error::UnknownTrackReference(_) => ErrorCode::UnknownTrackReference,
+            Error::TrackNotFound(_) => ErrorCode::TrackNotFound,
+            Error::BlockIndexOutOfRange { .. } => ErrorCode::BlockIndexOutOfRange,
+            Error::UnsupportedVersion { .. } => ErrorCode::UnsupportedVersion,
+            Error::InvalidCrop { .. } => ErrorCode::InvalidCrop,
+            Error::IncompleteMasteringMetadata { .. } => ErrorCode::IncompleteMasteringMetadata,
+            Error::DuplicateEncodingOrder(_) => ErrorCode::DuplicateEncodingOrder,
+            Error::DuplicateBlockAddId(_) => ErrorCode::DuplicateBlockAddId,
+            Error::TrackTypeMismatch { .. } => ErrorCode::TrackTypeMismatch,
+            Error::MissingFourcc(_) => ErrorCode::MissingFourcc,
+            Error::InvalidChapter { .. } => ErrorCode::InvalidChapter,
+            Error::InvalidLinking { .. } => ErrorCode::InvalidLinking,
+            Error::InvalidWebm { .. } => ErrorCode::InvalidWebm,
+            Error::AttachmentNotFound(_) => ErrorCode::AttachmentNotFound,
+        }
+    }
+
+    /// The error without the [`Error::InElement`] context.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::InElement { source, .. } => source.root(),
+            e => e,
+        }
+    }
+
+    /// Whether the reader ended before the element was complete, as opposed to malformed data.
+    ///
+    /// At a position where an element may start, this is how the end of the data shows up.
+    pub fn is_eof(&self) -> bool {
+        matches!(self.root(), Error::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof)
+    }
+
+    /// IDs of the master elements this error happened in, outermost first.
+    pub fn parents(&self) -> Vec<VInt64> {
+        let mut parents = vec![];
+        let mut error = self;
+        while let Error::InElement { parent, source } = error {
+            parents.push(*parent);
+            error = source;
+        }
+        parents
+    }
+
     /// Helper function to create a TryGetError with the requested and available sizes.
     #[inline]
     pub fn try_get_error(requested: usize, available: usize) -> Self {
@@ -63,3 +477,34 @@ impl Error {
 
 /// Result type for this crate.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        let missing = Error::MissingElement(VInt64::from_encoded(0x1A45DFA3));
+        let duplicate = Error::DuplicateElement {
+            id: VInt64::from_encoded(0x4286),
+            parent: VInt64::from_encoded(0x1A45DFA3),
+        };
+        assert_eq!(missing.code(), ErrorCode::MissingElement);
+        assert_eq!(duplicate.code(), ErrorCode::DuplicateElement);
+        assert_ne!(missing.code(), duplicate.code());
+        assert_eq!(Error::try_get_error(4, 2).code(), ErrorCode::TryGetError);
+    }
+
+    #[test]
+    fn test_is_eof() {
+        use crate::io::blocking_impl::ReadFrom;
+
+        let eof = VInt64::read_from(&mut &[][..]).unwrap_err();
+        assert!(eof.is_eof());
+        // a truncated 2-byte VInt
+        assert!(VInt64::read_from(&mut &[0x40][..]).unwrap_err().is_eof());
+        let malformed = VInt64::read_from(&mut &[0x00, 0x00][..]).unwrap_err();
+        assert!(matches!(malformed, Error::InvalidVInt));
+        assert!(!malformed.is_eof());
+    }
+}
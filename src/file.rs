@@ -0,0 +1,100 @@
+//! A fully loaded Matroska file.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::base::Header;
+use crate::element::Element;
+use crate::io::blocking_impl::{ReadElement, ReadFrom};
+use crate::master::{Ebml, Segment};
+use crate::*;
+
+/// A Matroska file loaded into memory: the EBML header followed by one or more Segments.
+///
+/// This is the fully loaded counterpart of `MatroskaView`, which skips the Clusters.
+/// It is written with [`WriteTo::write_to`](crate::io::blocking_impl::WriteTo::write_to).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MatroskaFile {
+    /// The EBML header.
+    pub ebml: Ebml,
+    /// The Segments, in file order.
+    pub segments: Vec<Segment>,
+}
+
+impl MatroskaFile {
+    /// Read the EBML header and every Segment until the end of the reader.
+    ///
+    /// Other Top-Level Elements, such as a Void after the last Segment, are skipped.
+    pub fn read_from<R: Read + Seek + ?Sized>(r: &mut R) -> crate::Result<Self> {
+        let ebml = Ebml::read_from(r)?;
+        let position = r.stream_position()?;
+        let end = r.seek(SeekFrom::End(0))?;
+        r.seek(SeekFrom::Start(position))?;
+
+        let mut segments = vec![];
+        while r.stream_position()? < end {
+            let header = Header::read_from(r)?;
+            if header.id == Segment::ID {
+                segments.push(Segment::read_element(&header, r)?);
+            } else {
+                // trailing Void or junk elements, as some muxers leave
+                header.skip(r)?;
+            }
+        }
+        if segments.is_empty() {
+            return Err(Error::MissingElement(Segment::ID));
+        }
+        Ok(Self { ebml, segments })
+    }
+}
+
+impl Encode for MatroskaFile {
+    fn encode<B: BufMut>(&self, buf: &mut B) -> crate::Result<()> {
+        self.ebml.encode(buf)?;
+        self.segments.encode(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::blocking_impl::WriteTo;
+    use crate::leaf::*;
+    use crate::master::*;
+    use crate::supplement::Void;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_round_trip() {
+        let segment = |title: &str| Segment {
+            info: Info {
+                title: Some(Title(title.to_string())),
+                ..Default::default()
+            },
+            cluster: vec![Cluster::default()],
+            ..Default::default()
+        };
+        let file = MatroskaFile {
+            ebml: Ebml::default(),
+            segments: vec![segment("first"), segment("second")],
+        };
+        let mut buf = vec![];
+        file.write_to(&mut buf).unwrap();
+
+        let read = MatroskaFile::read_from(&mut Cursor::new(&buf)).unwrap();
+        assert_eq!(read, file);
+
+        // a trailing Void is skipped
+        let mut padded = buf.clone();
+        Void { size: 16 }.write_to(&mut padded).unwrap();
+        let read = MatroskaFile::read_from(&mut Cursor::new(&padded)).unwrap();
+        assert_eq!(read, file);
+
+        // the EBML header alone is not a Matroska file
+        let mut ebml_only = vec![];
+        Ebml::default().write_to(&mut ebml_only).unwrap();
+        assert!(matches!(
+            MatroskaFile::read_from(&mut Cursor::new(&ebml_only)),
+            Err(Error::MissingElement(id)) if id == Segment::ID
+        ));
+    }
+}
@@ -1,10 +1,11 @@
+use std::borrow::Cow;
 use std::num::NonZero;
 
 use crate::{
     base::VInt64,
     lacer::Lacer,
-    leaf::SimpleBlock,
-    master::{BlockGroup, Cluster},
+    leaf::{ReferenceBlock, SimpleBlock},
+    master::{BlockGroup, Cluster, TrackEntry, Tracks},
     *,
 };
 
@@ -26,6 +27,54 @@ impl<'a> FrameData<'a> {
         FrameData::Multiple(data)
     }
 }
+/// A timestamp in Segment Ticks, the unit of Cluster and Block timestamps.
+///
+/// One tick lasts `TimestampScale` nanoseconds, use [`Ticks::to_ns`] and [`Ticks::from_ns`]
+/// to convert between the two.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ticks(pub i64);
+
+impl Ticks {
+    /// The timestamp in nanoseconds, given the Segment `TimestampScale`.
+    ///
+    /// Returns [`Error::InvalidTimestampScale`] for a scale of 0, and [`Error::TimestampOverflow`]
+    /// if the result does not fit in an `i64`.
+    pub fn to_ns(self, timestamp_scale: u64) -> crate::Result<i64> {
+        self.0
+            .checked_mul(checked_scale(timestamp_scale)?)
+            .ok_or(Error::TimestampOverflow { timestamp_scale })
+    }
+
+    /// The timestamp in ticks of a timestamp in nanoseconds, rounded down,
+    /// given the Segment `TimestampScale`.
+    ///
+    /// Returns [`Error::InvalidTimestampScale`] for a scale of 0.
+    pub fn from_ns(ns: i64, timestamp_scale: u64) -> crate::Result<Self> {
+        Ok(Self(ns.div_euclid(checked_scale(timestamp_scale)?)))
+    }
+}
+
+/// The `TimestampScale` as a positive `i64`, to compute with signed timestamps.
+fn checked_scale(timestamp_scale: u64) -> crate::Result<i64> {
+    i64::try_from(timestamp_scale)
+        .ok()
+        .filter(|&scale| scale > 0)
+        .ok_or(Error::InvalidTimestampScale(timestamp_scale))
+}
+
+impl std::ops::Deref for Ticks {
+    type Target = i64;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<i64> for Ticks {
+    fn from(ticks: i64) -> Self {
+        Self(ticks)
+    }
+}
+
 /// A Matroska Frame, representing a block(SimpleBlock/BlockGroup).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Frame<'a> {
@@ -39,10 +88,123 @@ pub struct Frame<'a> {
     pub is_discardable: bool,
     /// track number the frame belongs to
     pub track_number: u64,
-    /// timestamp of the frame, in the same timescale as the Cluster timestamp
-    pub timestamp: i64,
+    /// timestamp of the frame, in Segment Ticks like the Cluster timestamp
+    pub timestamp: Ticks,
     /// duration of the frame, in the same timescale as the Cluster timestamp
     pub duration: Option<NonZero<u64>>,
+    /// timestamps of the frames this frame depends on, relative to its own timestamp
+    ///
+    /// Only BlockGroups carry references; a frame with none is a keyframe.
+    pub references: &'a [ReferenceBlock],
+}
+
+impl<'a> Frame<'a> {
+    /// The frame data as text, for text subtitle tracks such as `S_TEXT/UTF8` or `S_TEXT/ASS`.
+    ///
+    /// Returns [`Error::InvalidUtf8`] if the data is not valid UTF-8, and
+    /// [`Error::MalformedLacingData`] for laced frames, as subtitles are never laced.
+    pub fn as_text(&self) -> crate::Result<&'a str> {
+        match self.data {
+            FrameData::Single(data) => Ok(std::str::from_utf8(data)?),
+            FrameData::Multiple(_) => Err(Error::MalformedLacingData),
+        }
+    }
+
+    /// Duration of the frame, in the same timescale as the Cluster timestamp.
+    ///
+    /// This is the BlockDuration when present, otherwise the `DefaultDuration` of the track
+    /// (per laced frame), converted from nanoseconds with the Segment `TimestampScale`.
+    /// Returns `None` if there is no duration, or if the total `DefaultDuration` overflows.
+    pub fn effective_duration(
+        &self,
+        track: Option<&TrackEntry>,
+        timestamp_scale: u64,
+    ) -> Option<u64> {
+        if let Some(duration) = self.duration {
+            return Some(duration.get());
+        }
+        let default_duration = *track?.default_duration?;
+        let count = match &self.data {
+            FrameData::Single(_) => 1,
+            FrameData::Multiple(frames) => frames.len() as u64,
+        };
+        Some(default_duration.checked_mul(count)? / timestamp_scale.max(1))
+    }
+}
+
+/// The flags byte of a SimpleBlock or Block header.
+///
+/// The keyframe and discardable bits are only meaningful for a SimpleBlock, they are reserved in a Block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BlockFlags(pub u8);
+
+impl BlockFlags {
+    const KEYFRAME: u8 = 0x80;
+    const INVISIBLE: u8 = 0x08;
+    const DISCARDABLE: u8 = 0x01;
+
+    /// Create the flags from their fields, with the reserved bits unset.
+    pub fn new(keyframe: bool, invisible: bool, lacing: Option<Lacer>, discardable: bool) -> Self {
+        let mut flags = 0;
+        if keyframe {
+            flags |= Self::KEYFRAME;
+        }
+        if invisible {
+            flags |= Self::INVISIBLE;
+        }
+        flags |= match lacing {
+            None => 0b00,
+            Some(Lacer::Xiph) => 0b01,
+            Some(Lacer::FixedSize) => 0b10,
+            Some(Lacer::Ebml) => 0b11,
+        } << 1;
+        if discardable {
+            flags |= Self::DISCARDABLE;
+        }
+        Self(flags)
+    }
+
+    /// Parse the flags from the byte in a block header.
+    pub fn from_byte(byte: u8) -> Self {
+        Self(byte)
+    }
+
+    /// The byte to write in a block header.
+    pub fn to_byte(self) -> u8 {
+        self.0
+    }
+
+    /// Whether the block contains only keyframes.
+    pub fn keyframe(self) -> bool {
+        self.0 & Self::KEYFRAME != 0
+    }
+
+    /// Whether the block is invisible.
+    pub fn invisible(self) -> bool {
+        self.0 & Self::INVISIBLE != 0
+    }
+
+    /// The lacing used in the block, `None` for no lacing.
+    pub fn lacing(self) -> Option<Lacer> {
+        Lacer::from_flag(self.0)
+    }
+
+    /// Whether the frames of the block can be discarded during playback.
+    pub fn discardable(self) -> bool {
+        self.0 & Self::DISCARDABLE != 0
+    }
+}
+
+impl From<u8> for BlockFlags {
+    fn from(byte: u8) -> Self {
+        Self::from_byte(byte)
+    }
+}
+
+impl From<BlockFlags> for u8 {
+    fn from(flags: BlockFlags) -> Self {
+        flags.to_byte()
+    }
 }
 
 /// A block in a Cluster, either a SimpleBlock or a BlockGroup.
@@ -51,6 +213,7 @@ pub struct Frame<'a> {
 /// * when reading: often we just want to iterate over all blocks in a cluster, regardless of type.
 /// * when writing: we may want to write a list of blocks of mixed types.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)] // boxing BlockGroup would get in the way of pattern matching
 pub enum ClusterBlock {
     /// A SimpleBlock
     Simple(SimpleBlock),
@@ -64,6 +227,11 @@ impl ClusterBlock {
             ClusterBlock::Group(b) => BlockRef::Group(b),
         }
     }
+
+    /// The frame of the block, in a Cluster with the given timestamp.
+    pub(crate) fn frame(&self, cluster_ts: u64) -> crate::Result<Frame<'_>> {
+        self.block_ref().into_frame(cluster_ts)
+    }
 }
 impl From<SimpleBlock> for ClusterBlock {
     fn from(b: SimpleBlock) -> Self {
@@ -98,22 +266,20 @@ impl<'a> BlockRef<'a> {
                 let body_buf = &mut &block[..];
                 let track_number = VInt64::decode(body_buf)?;
                 let relative_timestamp = body_buf.try_get_i16()?;
-                let flag = body_buf.try_get_u8()?;
+                let flags = BlockFlags::from_byte(body_buf.try_get_u8()?);
                 let data = *body_buf;
-                let lacing = (flag >> 1) & 0x03;
                 Ok(Frame {
-                    data: match lacing {
-                        0 => FrameData::single(data),
-                        0b01 => FrameData::multiple(Lacer::Xiph.delace(data)?),
-                        0b11 => FrameData::multiple(Lacer::Ebml.delace(data)?),
-                        _ => FrameData::multiple(Lacer::FixedSize.delace(data)?),
+                    data: match flags.lacing() {
+                        None => FrameData::single(data),
+                        Some(lacer) => FrameData::multiple(lacer.delace(data)?),
                     },
-                    is_keyframe: (flag & 0x80) != 0,
-                    is_invisible: (flag & 0x08) != 0,
-                    is_discardable: (flag & 0x01) != 0,
+                    is_keyframe: flags.keyframe(),
+                    is_invisible: flags.invisible(),
+                    is_discardable: flags.discardable(),
                     track_number: *track_number,
-                    timestamp: cluster_ts as i64 + relative_timestamp as i64,
+                    timestamp: Ticks(cluster_ts as i64 + relative_timestamp as i64),
                     duration: None,
+                    references: &[],
                 })
             }
             BlockRef::Group(g) => {
@@ -121,23 +287,20 @@ impl<'a> BlockRef<'a> {
                 let body_buf = &mut &block[..];
                 let track_number = VInt64::decode(body_buf)?;
                 let relative_timestamp = body_buf.try_get_i16()?;
-                let flag = body_buf.try_get_u8()?;
+                let flags = BlockFlags::from_byte(body_buf.try_get_u8()?);
                 let data = *body_buf;
-                let lacing = (flag >> 1) & 0x03;
-
                 Ok(Frame {
-                    data: match lacing {
-                        0 => FrameData::single(data),
-                        0b01 => FrameData::multiple(Lacer::Xiph.delace(data)?),
-                        0b11 => FrameData::multiple(Lacer::Ebml.delace(data)?),
-                        _ => FrameData::multiple(Lacer::FixedSize.delace(data)?),
+                    data: match flags.lacing() {
+                        None => FrameData::single(data),
+                        Some(lacer) => FrameData::multiple(lacer.delace(data)?),
                     },
                     is_keyframe: g.reference_block.is_empty(),
-                    is_invisible: flag & 0x08 != 0,
+                    is_invisible: flags.invisible(),
                     is_discardable: false,
                     track_number: *track_number,
-                    timestamp: cluster_ts as i64 + relative_timestamp as i64,
+                    timestamp: Ticks(cluster_ts as i64 + relative_timestamp as i64),
                     duration: g.block_duration.and_then(|d| NonZero::new(*d)),
+                    references: &g.reference_block,
                 })
             }
         }
@@ -155,11 +318,343 @@ impl<'a> From<&'a crate::master::BlockGroup> for BlockRef<'a> {
     }
 }
 
+/// Options for [`Cluster::frames_with`], see [`TrackEntry::decode_frame_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameOptions {
+    /// Undo the compressions of the tracks: header stripping, and zlib with the `zlib` feature.
+    pub decompress: bool,
+    /// AES-128 key to decrypt the tracks encrypted as in WebM, with the `encryption` feature.
+    pub decrypt_key: Option<[u8; 16]>,
+}
+
+/// A frame with its payloads ready to decode, from [`Cluster::frames_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedFrame<'a> {
+    /// The frame as read from the block.
+    pub frame: Frame<'a>,
+    /// The payloads of the frame, one per laced frame. They borrow from the block unless
+    /// the frame had to be transformed.
+    pub payloads: Vec<Cow<'a, [u8]>>,
+}
+
 impl Cluster {
     /// frames in the cluster.
     pub fn frames(&self) -> impl Iterator<Item = crate::Result<Frame<'_>>> + '_ {
-        self.blocks
-            .iter()
-            .map(|b| b.block_ref().into_frame(*self.timestamp))
+        self.blocks.iter().map(|b| b.frame(*self.timestamp))
+    }
+
+    /// Frames of `track` in the cluster, with the keyframe flag as players see it.
+    ///
+    /// Every frame of an audio track (`TrackType` 2) is a keyframe, even when the encoder left
+    /// the keyframe bit of the SimpleBlock unset. Errors of blocks that cannot be read are kept,
+    /// as their track is unknown.
+    pub fn frames_for_track(
+        &self,
+        track: &TrackEntry,
+    ) -> impl Iterator<Item = crate::Result<Frame<'_>>> + '_ {
+        let track_number = *track.track_number;
+        let is_audio = *track.track_type == 2;
+        self.frames()
+            .filter(move |frame| match frame {
+                Ok(frame) => frame.track_number == track_number,
+                Err(_) => true,
+            })
+            .map(move |frame| {
+                let mut frame = frame?;
+                frame.is_keyframe |= is_audio;
+                Ok(frame)
+            })
+    }
+
+    /// Frames in the cluster with their payloads processed according to `options`,
+    /// looking up the track of each frame in `tracks`.
+    ///
+    /// Returns [`Error::UnknownTrackReference`] for a frame of a track missing from `tracks`.
+    pub fn frames_with<'a>(
+        &'a self,
+        tracks: &'a Tracks,
+        options: FrameOptions,
+    ) -> impl Iterator<Item = crate::Result<DecodedFrame<'a>>> + 'a {
+        self.frames().map(move |frame| {
+            let frame = frame?;
+            let track = tracks
+                .by_number(frame.track_number)
+                .ok_or(Error::UnknownTrackReference(frame.track_number))?;
+            let payloads = match &frame.data {
+                FrameData::Single(data) => vec![*data],
+                FrameData::Multiple(data) => data.clone(),
+            };
+            let payloads = payloads
+                .into_iter()
+                .map(
+                    |data| match options.decompress || options.decrypt_key.is_some() {
+                        true => track.decode_frame_with(data, &options),
+                        false => Ok(Cow::Borrowed(data)),
+                    },
+                )
+                .collect::<crate::Result<_>>()?;
+            Ok(DecodedFrame { frame, payloads })
+        })
+    }
+}
+
+/// Interleave the frames of multiple tracks into a single list ordered by timestamp.
+///
+/// Frames with the same timestamp are ordered by track number, and frames of the same track
+/// keep their original relative order. This is the ordering a muxer needs to write A/V blocks.
+pub fn interleave<'a>(tracks: Vec<Vec<Frame<'a>>>) -> Vec<Frame<'a>> {
+    let mut frames: Vec<_> = tracks.into_iter().flatten().collect();
+    // stable sort, so frames of one track with equal timestamps stay in order
+    frames.sort_by_key(|f| (f.timestamp, f.track_number));
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leaf::{Block, Timestamp};
+
+    fn frame(track_number: u64, timestamp: i64) -> Frame<'static> {
+        Frame {
+            data: FrameData::Single(&[]),
+            is_keyframe: true,
+            is_invisible: false,
+            is_discardable: false,
+            track_number,
+            timestamp: Ticks(timestamp),
+            duration: None,
+            references: &[],
+        }
+    }
+
+    #[test]
+    fn test_interleave() {
+        let video = (0..5).map(|i| frame(1, i * 40)).collect::<Vec<_>>();
+        let audio = (0..10).map(|i| frame(2, i * 20)).collect::<Vec<_>>();
+        let frames = interleave(vec![audio, video]);
+        assert_eq!(frames.len(), 15);
+        assert!(frames.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+        // ties are broken by track number
+        assert_eq!(
+            frames
+                .iter()
+                .filter(|f| *f.timestamp == 40)
+                .map(|f| f.track_number)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_frames_for_track() {
+        use crate::leaf::{TrackNumber, TrackType};
+
+        // keyframe bit unset on both blocks
+        let cluster = Cluster {
+            blocks: vec![
+                SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x00, 0x00, 1])).into(),
+                SimpleBlock(Bytes::from_static(&[0x82, 0x00, 0x00, 0x00, 2])).into(),
+            ],
+            ..Default::default()
+        };
+        let track = |number: u64, track_type: u64| TrackEntry {
+            track_number: TrackNumber(number),
+            track_type: TrackType(track_type),
+            ..Default::default()
+        };
+        assert!(cluster.frames().all(|f| !f.unwrap().is_keyframe));
+
+        let audio: Vec<_> = cluster.frames_for_track(&track(2, 2)).collect();
+        assert_eq!(audio.len(), 1);
+        let frame = audio[0].as_ref().unwrap();
+        assert_eq!(frame.track_number, 2);
+        assert!(frame.is_keyframe);
+
+        let video: Vec<_> = cluster.frames_for_track(&track(1, 1)).collect();
+        assert_eq!(video.len(), 1);
+        assert!(!video[0].as_ref().unwrap().is_keyframe);
+    }
+
+    #[test]
+    fn test_fixed_size_lacing() {
+        // keyframe with fixed-size lacing (0b10), 3 frames of 2 bytes
+        let cluster = Cluster {
+            blocks: vec![
+                SimpleBlock(Bytes::from_static(&[
+                    0x81, 0x00, 0x00, 0x84, 2, 1, 1, 2, 2, 3, 3,
+                ]))
+                .into(),
+            ],
+            ..Default::default()
+        };
+        let frame = cluster.frames().next().unwrap().unwrap();
+        assert!(frame.is_keyframe);
+        assert_eq!(
+            frame.data,
+            FrameData::Multiple(vec![&[1, 1], &[2, 2], &[3, 3]])
+        );
+
+        assert_eq!(Lacer::from_flag(0x80), None);
+        assert_eq!(Lacer::from_flag(0x82), Some(Lacer::Xiph));
+        assert_eq!(Lacer::from_flag(0x84), Some(Lacer::FixedSize));
+        assert_eq!(Lacer::from_flag(0x86), Some(Lacer::Ebml));
+    }
+
+    #[test]
+    fn test_ticks() {
+        // 100µs ticks, as in the second IETF test file
+        let scale = 100_000;
+        assert_eq!(Ticks(25).to_ns(scale).unwrap(), 2_500_000);
+        assert_eq!(Ticks::from_ns(2_500_000, scale).unwrap(), Ticks(25));
+        assert_eq!(Ticks::from_ns(2_599_999, scale).unwrap(), Ticks(25));
+        assert_eq!(Ticks::from_ns(-1, scale).unwrap(), Ticks(-1));
+        assert_eq!(Ticks(-3).to_ns(scale).unwrap(), -300_000);
+
+        // overflows and invalid scales are errors, not panics
+        assert!(matches!(
+            Ticks(i64::MAX).to_ns(scale),
+            Err(Error::TimestampOverflow { timestamp_scale }) if timestamp_scale == scale
+        ));
+        assert!(matches!(
+            Ticks(1).to_ns(0),
+            Err(Error::InvalidTimestampScale(0))
+        ));
+        assert!(matches!(
+            Ticks::from_ns(1, 0),
+            Err(Error::InvalidTimestampScale(0))
+        ));
+        assert!(matches!(
+            Ticks::from_ns(1, u64::MAX),
+            Err(Error::InvalidTimestampScale(u64::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_block_group_references() {
+        let cluster = Cluster {
+            timestamp: Timestamp(1000),
+            blocks: vec![
+                BlockGroup {
+                    block: Block(Bytes::from_static(&[0x81, 0x00, 0x28, 0x00, 1])),
+                    reference_block: vec![ReferenceBlock(-40), ReferenceBlock(40)],
+                    ..Default::default()
+                }
+                .into(),
+                SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x00, 0x80, 2])).into(),
+            ],
+            ..Default::default()
+        };
+        let frames: Vec<_> = cluster.frames().collect::<crate::Result<_>>().unwrap();
+        assert!(!frames[0].is_keyframe);
+        assert_eq!(
+            frames[0].references.iter().map(|r| **r).collect::<Vec<_>>(),
+            vec![-40, 40]
+        );
+        assert!(frames[1].is_keyframe);
+        assert!(frames[1].references.is_empty());
+    }
+
+    #[test]
+    fn test_frames_with_header_stripping() {
+        use crate::leaf::*;
+        use crate::master::{ContentCompression, ContentEncoding, ContentEncodings};
+
+        let stripped = TrackEntry {
+            track_number: TrackNumber(1),
+            content_encodings: Some(ContentEncodings {
+                content_encoding: vec![ContentEncoding {
+                    content_compression: Some(ContentCompression {
+                        content_comp_algo: ContentCompAlgo(3),
+                        content_comp_settings: Some(ContentCompSettings(Bytes::from_static(&[
+                            0x00, 0x00, 0x01,
+                        ]))),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let bzlib = TrackEntry {
+            track_number: TrackNumber(3),
+            content_encodings: Some(ContentEncodings {
+                content_encoding: vec![ContentEncoding {
+                    content_compression: Some(ContentCompression {
+                        content_comp_algo: ContentCompAlgo(1),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let tracks = Tracks {
+            track_entry: vec![
+                stripped,
+                TrackEntry {
+                    track_number: TrackNumber(2),
+                    ..Default::default()
+                },
+                bzlib,
+            ],
+            ..Default::default()
+        };
+        let mut cluster = Cluster::default();
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[0x81, 0, 0, 0x80, 0x65])));
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[0x82, 0, 0, 0x80, 0xAA])));
+
+        let decompress = FrameOptions {
+            decompress: true,
+            ..Default::default()
+        };
+        let frames: Vec<_> = cluster
+            .frames_with(&tracks, decompress)
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(&*frames[0].payloads[0], [0x00, 0x00, 0x01, 0x65]);
+        assert!(matches!(frames[0].payloads[0], Cow::Owned(_)));
+        assert!(matches!(frames[1].payloads[0], Cow::Borrowed([0xAA])));
+
+        let raw: Vec<_> = cluster
+            .frames_with(&tracks, FrameOptions::default())
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(&*raw[0].payloads[0], [0x65]);
+
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[0x83, 0, 0, 0x80, 0x78])));
+        cluster.push_block(SimpleBlock(Bytes::from_static(&[0x84, 0, 0, 0x80, 0x00])));
+        let errors: Vec<_> = cluster
+            .frames_with(&tracks, decompress)
+            .skip(2)
+            .map(|f| f.unwrap_err())
+            .collect();
+        assert!(matches!(errors[0], Error::UnsupportedContentEncoding(3)));
+        assert!(matches!(errors[1], Error::UnknownTrackReference(4)));
+    }
+
+    #[test]
+    fn test_block_flags_round_trip() {
+        for byte in 0..=u8::MAX {
+            let flags = BlockFlags::from_byte(byte);
+            assert_eq!(flags.to_byte(), byte);
+            assert_eq!(u8::from(BlockFlags::from(byte)), byte);
+            // rebuilding from the fields only loses the reserved bits
+            let rebuilt = BlockFlags::new(
+                flags.keyframe(),
+                flags.invisible(),
+                flags.lacing(),
+                flags.discardable(),
+            );
+            assert_eq!(rebuilt.to_byte(), byte & 0b1000_1111);
+        }
+
+        let flags = BlockFlags::new(true, false, Some(Lacer::Ebml), true);
+        assert_eq!(flags.to_byte(), 0x87);
+        assert!(flags.keyframe());
+        assert!(!flags.invisible());
+        assert_eq!(flags.lacing(), Some(Lacer::Ebml));
+        assert!(flags.discardable());
     }
 }
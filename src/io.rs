@@ -1,14 +1,51 @@
 //! I/O utilities.
+//!
+//! `blocking` and `tokio` are shorter aliases of [`blocking_impl`] and `tokio_impl`.
+//! The blocking traits are also re-exported here, for custom parsers working on any
+//! [`std::io::Read`]:
+//!
+//! ```
+//! use mkv_element::io::{ReadExt, ReadFrom};
+//! use mkv_element::prelude::*;
+//!
+//! // a reader yielding a 2-byte VInt, then a single byte
+//! struct Bytes(Vec<u8>);
+//! impl std::io::Read for Bytes {
+//!     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+//!         let n = buf.len().min(self.0.len());
+//!         buf[..n].copy_from_slice(&self.0[..n]);
+//!         self.0.drain(..n);
+//!         Ok(n)
+//!     }
+//! }
+//!
+//! let mut reader = Bytes(vec![0x41, 0x00, 0x2A]);
+//! assert_eq!(*VInt64::read_from(&mut reader)?, 256);
+//! assert_eq!(reader.read_u8()?, 0x2A);
+//! assert!(reader.read_vint().unwrap_err().is_eof());
+//! # Ok::<(), mkv_element::Error>(())
+//! ```
+
+/// Alias of [`blocking_impl`].
+pub use blocking_impl as blocking;
+pub use blocking_impl::{
+    ReadElement, ReadExt, ReadFrom, ReadFromWith, WriteElement, WriteTo, peek_doc_type,
+};
+/// Alias of [`tokio_impl`].
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use tokio_impl as tokio;
 
 /// blocking I/O implementations, supporting reading and writing.
 pub mod blocking_impl {
     use crate::{
-        base::Header,
-        element::Element,
-        master::{Cluster, Segment},
+        base::{Header, VInt64},
+        element::{DecodeOptions, Element, MasterElement},
+        leaf::DocType,
+        master::{Cluster, Ebml, Segment},
         *,
     };
-    use std::io::{Read, Write};
+    use std::io::{Read, Seek, SeekFrom, Write};
 
     /// Read from a reader.
     pub trait ReadFrom: Sized {
@@ -16,6 +53,31 @@ pub mod blocking_impl {
         fn read_from<R: Read + ?Sized>(r: &mut R) -> crate::Result<Self>;
     }
 
+    /// Byte-reading helpers, implemented for every reader.
+    pub trait ReadExt: Read {
+        /// Read a single byte.
+        fn read_u8(&mut self) -> crate::Result<u8> {
+            let mut byte = [0u8; 1];
+            self.read_exact(&mut byte)?;
+            Ok(byte[0])
+        }
+
+        /// Read a variable-length integer, see [`VInt64`].
+        fn read_vint(&mut self) -> crate::Result<VInt64> {
+            VInt64::read_from(self)
+        }
+    }
+    impl<R: Read + ?Sized> ReadExt for R {}
+
+    /// Read from a reader with the given decode options.
+    pub trait ReadFromWith: Sized {
+        /// Read Self from a reader with the given decode options.
+        fn read_from_with<R: Read + ?Sized>(
+            r: &mut R,
+            options: &DecodeOptions,
+        ) -> crate::Result<Self>;
+    }
+
     /// Read an element from a reader provided the header.
     pub trait ReadElement: Sized + Element {
         /// Read an element from a reader provided the header.
@@ -44,6 +106,90 @@ pub mod blocking_impl {
             }
             Ok(buf)
         }
+
+        /// Advance a reader past the body of the element, seeking instead of reading it.
+        ///
+        /// Seeking past the end of the reader is not an error, as with [`Seek::seek`].
+        /// Use [`Header::skip_read`] for readers that cannot seek.
+        pub fn skip<R: Read + Seek + ?Sized>(&self, r: &mut R) -> crate::Result<()> {
+            if self.size.is_unknown {
+                return Err(crate::Error::ElementBodySizeUnknown(self.id));
+            }
+            r.seek(SeekFrom::Current(*self.size as i64))?;
+            Ok(())
+        }
+
+        /// Advance a reader past the body of the element, even when its size is unknown.
+        ///
+        /// An element of unknown size ends at the first element that cannot be one of its
+        /// children, here any of `siblings`. Its body is scanned one child header at a time:
+        /// children of known size are skipped, and children of unknown size are entered, as they
+        /// end at the same sibling. The reader is left at the start of that sibling, or at the
+        /// end of the reader if there is none.
+        pub fn skip_to_sibling<R: Read + Seek + ?Sized>(
+            &self,
+            r: &mut R,
+            siblings: &[VInt64],
+        ) -> crate::Result<()> {
+            if !self.size.is_unknown {
+                return self.skip(r);
+            }
+            loop {
+                let position = r.stream_position()?;
+                let child = match Header::read_from(r) {
+                    Ok(child) => child,
+                    Err(e) if e.is_eof() => {
+                        r.seek(SeekFrom::Start(position))?;
+                        return Ok(());
+                    }
+                    Err(e) => return Err(e),
+                };
+                if siblings.contains(&child.id) {
+                    r.seek(SeekFrom::Start(position))?;
+                    return Ok(());
+                }
+                if !child.size.is_unknown {
+                    child.skip(r)?;
+                }
+            }
+        }
+
+        /// Advance a reader past the body of the element, reading and discarding it.
+        pub fn skip_read<R: Read + ?Sized>(&self, r: &mut R) -> crate::Result<()> {
+            if self.size.is_unknown {
+                return Err(crate::Error::ElementBodySizeUnknown(self.id));
+            }
+            let size = *self.size;
+            let n = std::io::copy(&mut r.take(size), &mut std::io::sink())?;
+            if size != n {
+                return Err(crate::Error::try_get_error(size as usize, n as usize));
+            }
+            Ok(())
+        }
+    }
+
+    /// Read only the `DocType` from an EBML header, without decoding the whole `Ebml` element.
+    ///
+    /// The reader must be positioned at the start of the EBML header. Scanning stops as soon as
+    /// the `DocType` element is found, leaving the reader inside the EBML header body.
+    /// Returns `None` if the EBML header has no `DocType`.
+    pub fn peek_doc_type<R: Read + ?Sized>(r: &mut R) -> crate::Result<Option<String>> {
+        let header = Header::read_from(r)?;
+        if header.id != Ebml::ID {
+            return Err(crate::Error::MissingElement(Ebml::ID));
+        }
+        if header.size.is_unknown {
+            return Err(crate::Error::ElementBodySizeUnknown(header.id));
+        }
+        let mut body = r.take(*header.size);
+        while body.limit() > 0 {
+            let child = Header::read_from(&mut body)?;
+            if child.id == DocType::ID {
+                return Ok(Some(DocType::read_element(&child, &mut body)?.0));
+            }
+            child.skip_read(&mut body)?;
+        }
+        Ok(None)
     }
 
     /// Write to a writer.
@@ -76,22 +222,57 @@ pub mod blocking_impl {
             w.write_all(&buf)?;
             Ok(())
         }
+
+        /// Write a master element with a CRC-32 of its body as first child.
+        ///
+        /// The CRC-32 is computed over the encoded children, replacing the `crc32` of the element
+        /// if any, so it always matches what is written. Leaf elements have no children to cover:
+        ///
+        /// ```compile_fail
+        /// use mkv_element::io::WriteElement;
+        /// use mkv_element::prelude::*;
+        /// Title("leaf".to_string()).write_element_with_crc(&mut vec![]).unwrap();
+        /// ```
+        fn write_element_with_crc<W: Write + ?Sized>(&self, w: &mut W) -> crate::Result<()>
+        where
+            Self: MasterElement,
+        {
+            w.write_all(&encode_with_crc(self)?)?;
+            Ok(())
+        }
     }
     impl<T: Element> WriteElement for T {}
+
+    /// Encode a master element with a freshly computed CRC-32 as first child.
+    ///
+    /// The CRC-32 the element may carry is dropped before encoding the children it covers.
+    pub(crate) fn encode_with_crc<T: MasterElement>(element: &T) -> crate::Result<Vec<u8>> {
+        use crate::supplement::Crc32;
+
+        let mut element = element.clone();
+        element.set_crc32(None);
+        let mut children = vec![];
+        element.encode_body(&mut children)?;
+        element.set_crc32(Some(Crc32::compute(&children)));
+        let mut buf = vec![];
+        element.encode(&mut buf)?;
+        Ok(buf)
+    }
 }
 /// tokio non-blocking I/O implementations, supporting async reading and writing.
 #[cfg(feature = "tokio")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
 pub mod tokio_impl {
     use crate::{
-        base::Header,
-        element::Element,
+        base::{Header, VInt64},
+        element::{DecodeOptions, Element, MasterElement},
         master::{Cluster, Segment},
         *,
     };
 
     use std::future::Future;
-    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+    use std::io::SeekFrom;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWriteExt};
 
     /// Read from a reader asynchronously.
     pub trait AsyncReadFrom: Sized {
@@ -101,6 +282,15 @@ pub mod tokio_impl {
         ) -> impl Future<Output = crate::Result<Self>>;
     }
 
+    /// Read from a reader asynchronously with the given decode options.
+    pub trait AsyncReadFromWith: Sized {
+        /// Read Self from a reader with the given decode options.
+        fn async_read_from_with<R: tokio::io::AsyncRead + Unpin + ?Sized>(
+            r: &mut R,
+            options: &DecodeOptions,
+        ) -> impl Future<Output = crate::Result<Self>>;
+    }
+
     /// Read an element from a reader provided the header asynchronously.
     pub trait AsyncReadElement: Sized + Element {
         /// Read an element from a reader provided the header.
@@ -152,6 +342,22 @@ pub mod tokio_impl {
                 Ok(w.write_all(&buf).await?)
             }
         }
+
+        /// Write a master element with a CRC-32 of its body as first child asynchronously.
+        ///
+        /// See [`WriteElement::write_element_with_crc`](crate::io::blocking_impl::WriteElement::write_element_with_crc).
+        fn async_write_element_with_crc<W: tokio::io::AsyncWrite + Unpin + ?Sized>(
+            &self,
+            w: &mut W,
+        ) -> impl std::future::Future<Output = crate::Result<()>>
+        where
+            Self: MasterElement,
+        {
+            async {
+                let buf = crate::io::blocking_impl::encode_with_crc(self)?;
+                Ok(w.write_all(&buf).await?)
+            }
+        }
     }
     impl<T: Element> AsyncWriteElement for T {}
 
@@ -176,5 +382,40 @@ pub mod tokio_impl {
             }
             Ok(buf)
         }
+
+        /// Advance a reader past the body of the element, even when its size is unknown.
+        ///
+        /// See [`Header::skip_to_sibling`](crate::base::Header::skip_to_sibling).
+        pub(crate) async fn skip_to_sibling_tokio<R>(
+            &self,
+            r: &mut R,
+            siblings: &[VInt64],
+        ) -> crate::Result<()>
+        where
+            R: AsyncRead + AsyncSeek + Unpin + ?Sized,
+        {
+            if !self.size.is_unknown {
+                r.seek(SeekFrom::Current(*self.size as i64)).await?;
+                return Ok(());
+            }
+            loop {
+                let position = r.stream_position().await?;
+                let child = match Header::async_read_from(r).await {
+                    Ok(child) => child,
+                    Err(e) if e.is_eof() => {
+                        r.seek(SeekFrom::Start(position)).await?;
+                        return Ok(());
+                    }
+                    Err(e) => return Err(e),
+                };
+                if siblings.contains(&child.id) {
+                    r.seek(SeekFrom::Start(position)).await?;
+                    return Ok(());
+                }
+                if !child.size.is_unknown {
+                    r.seek(SeekFrom::Current(*child.size as i64)).await?;
+                }
+            }
+        }
     }
 }
@@ -4,6 +4,7 @@ use crate::{Error, base::VInt64, io::blocking_impl::ReadFrom, *};
 
 // https://www.matroska.org/technical/notes.html
 /// Handler for lacing and delacing operations on frame data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Lacer {
     /// Xiph lacing (variable-size frames with size prefixes)
     ///
@@ -35,6 +36,16 @@ pub enum Lacer {
 }
 
 impl Lacer {
+    /// The lacer selected by the lacing bits (bits 5-6) of a Block header flag, `None` for no lacing.
+    pub fn from_flag(flag: u8) -> Option<Self> {
+        match (flag >> 1) & 0b11 {
+            0b00 => None,
+            0b01 => Some(Lacer::Xiph),
+            0b10 => Some(Lacer::FixedSize),
+            _ => Some(Lacer::Ebml),
+        }
+    }
+
     /// Encode multiple frames into a single laced block
     pub fn lace(&self, frames: &[&[u8]]) -> Vec<u8> {
         if frames.is_empty() {
@@ -130,6 +141,10 @@ impl Lacer {
     }
 
     /// Decode a laced block into individual frames
+    ///
+    /// A lace of a single frame, i.e. a lacing head of 0, is accepted by every lacer and yields
+    /// the rest of the data as the frame: the specification allows it, and muxers do write it,
+    /// as [`Lacer::lace`] does for a single frame. Empty data yields no frame.
     pub fn delace<'a>(&self, data: &'a [u8]) -> crate::Result<Vec<&'a [u8]>> {
         // TODO(perf): avoid heap allocations ideally
         // we should be able to return a `impl Iterator<Item = crate::Result<&'a [u8]>>` here
@@ -140,33 +155,21 @@ impl Lacer {
             return Ok(vec![]);
         }
         let num_frames = data[0] as usize + 1;
+        // no sizes are stored for a single frame, whatever the lacer
         if num_frames == 1 {
             return Ok(vec![&data[1..]]);
         }
 
         match self {
             Lacer::Xiph => {
-                let mut out = Vec::with_capacity(num_frames);
-
-                let data_start_pos = data
-                    .iter()
-                    .enumerate()
-                    .skip(1)
-                    .filter(|(_, b)| **b != 0xFF)
-                    .nth(num_frames - 2)
-                    .map(|(i, _)| i)
-                    .ok_or(Error::MalformedLacingData)?
-                    + 1;
-
-                let laced_data = data
-                    .get(data_start_pos..)
-                    .ok_or(Error::MalformedLacingData)?;
+                let mut sizes_buf = &data[1..];
+                let sizes: Vec<XiphSize> = decode_n(&mut sizes_buf, num_frames - 1)
+                    .map_err(|_| Error::MalformedLacingData)?;
+                let laced_data = sizes_buf;
 
+                let mut out = Vec::with_capacity(num_frames);
                 let mut start = 0;
-                for size in data[1..data_start_pos]
-                    .split_inclusive(|b| *b != 0xFF)
-                    .map(|chunk| chunk.iter().map(|b| *b as usize).sum::<usize>())
-                {
+                for XiphSize(size) in sizes {
                     out.push(
                         laced_data
                             .get(start..start + size)
@@ -227,6 +230,22 @@ impl Lacer {
     }
 }
 
+/// A frame size in Xiph lacing: a run of 0xFF octets ended by an octet below 0xFF, all summed.
+struct XiphSize(usize);
+
+impl Decode for XiphSize {
+    fn decode(buf: &mut dyn Buf) -> crate::Result<Self> {
+        let mut size = 0;
+        loop {
+            let octet = buf.try_get_u8()?;
+            size += octet as usize;
+            if octet != 0xFF {
+                return Ok(Self(size));
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod lacer_tests {
     use super::*;
@@ -234,7 +253,7 @@ mod lacer_tests {
     fn test_xiph_lacing() {
         // 0 frames
         let laced = Lacer::Xiph.lace(&[]);
-        assert_eq!(laced, vec![]);
+        assert_eq!(laced, Vec::<u8>::new());
         let frames: Vec<_> = Lacer::Xiph.delace(&[]).unwrap();
         assert_eq!(frames.len(), 0);
 
@@ -307,7 +326,7 @@ mod lacer_tests {
     fn test_ebml_lacing() {
         // 0 frames
         let laced = Lacer::Ebml.lace(&[]);
-        assert_eq!(laced, vec![]);
+        assert_eq!(laced, Vec::<u8>::new());
         let frames: Vec<_> = Lacer::Ebml.delace(&[]).unwrap();
         assert_eq!(frames.len(), 0);
 
@@ -368,7 +387,7 @@ mod lacer_tests {
     fn test_fixed_size_lacing() {
         // 0 frames
         let laced = Lacer::FixedSize.lace(&[]);
-        assert_eq!(laced, vec![]);
+        assert_eq!(laced, Vec::<u8>::new());
         let frames: Vec<_> = Lacer::FixedSize.delace(&[]).unwrap();
         assert_eq!(frames.len(), 0);
 
@@ -387,4 +406,20 @@ mod lacer_tests {
         assert_eq!(frames[1], &[42u8; 500]);
         assert_eq!(frames[2], &[38u8; 500]);
     }
+
+    #[test]
+    fn test_single_frame_lacing() {
+        let frame = [1u8, 2, 3, 0xFF, 0x00];
+        for lacer in [Lacer::Xiph, Lacer::FixedSize, Lacer::Ebml] {
+            let laced = lacer.lace(&[&frame]);
+            assert_eq!(laced, [&[0x00][..], &frame].concat(), "{lacer:?}");
+            assert_eq!(lacer.delace(&laced).unwrap(), vec![&frame[..]], "{lacer:?}");
+            // an empty frame is still one frame
+            assert_eq!(
+                lacer.delace(&[0x00]).unwrap(),
+                vec![&[][..] as &[u8]],
+                "{lacer:?}"
+            );
+        }
+    }
 }
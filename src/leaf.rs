@@ -37,6 +37,8 @@ mod tests {
     }
     #[test]
     fn test_sint() {
+        use crate::element::{DecodeOptions, Strictness};
+        let strict = DecodeOptions::with_strictness(Strictness::Strict);
         assert_eq!(-2i64.pow(15), -32768);
 
         let positive = |n: u32| 2i64.pow((n * 8) - 1) - 1;
@@ -52,6 +54,9 @@ mod tests {
             (vec![0xFC, 0x18], -1000),
             (vec![0x7F], 127),                                 // 2^7 - 1
             (vec![0x80], -128),                                // -2^7
+            (vec![0x00, 0x80], 128),                           // 2^7
+            (vec![0xFF, 0x7F], -129),                          // -2^7 - 1
+            (vec![0x00, 0x80, 0x00], 32768),                   // 2^15
             (vec![0x7F, 0xFF], positive(2)),                   // 2^15 - 1
             (vec![0x80, 0x00], negative(2)),                   // -2^15
             (vec![0x7F, 0xFF, 0xFF], positive(3)),             // 2^23 - 1
@@ -76,6 +81,11 @@ mod tests {
             let mut buf = vec![];
             ReferenceBlock(decoded).encode_body(&mut buf).unwrap();
             assert_eq!(buf, encoded);
+            // what is written is accepted by the strict decoder
+            assert_eq!(
+                ReferenceBlock::decode_body_with(&mut &buf[..], &strict).unwrap(),
+                ReferenceBlock(decoded)
+            );
         }
     }
 
@@ -106,6 +116,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_float_extended() {
+        use crate::element::{DecodeOptions, Strictness};
+        let lenient = DecodeOptions::with_strictness(Strictness::Lenient);
+        let test_pair = [
+            // 1.0
+            ([0x3F, 0xFF, 0x80, 0, 0, 0, 0, 0, 0, 0], 1.0),
+            // -2.5
+            ([0xC0, 0x00, 0xA0, 0, 0, 0, 0, 0, 0, 0], -2.5),
+            // pi
+            (
+                [0x40, 0x00, 0xC9, 0x0F, 0xDA, 0xA2, 0x21, 0x68, 0xC2, 0x35],
+                std::f64::consts::PI,
+            ),
+            // 44100.0
+            ([0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0], 44100.0),
+            ([0; 10], 0.0),
+            ([0x7F, 0xFF, 0x80, 0, 0, 0, 0, 0, 0, 0], f64::INFINITY),
+        ];
+        for (encoded, decoded) in test_pair {
+            let v = Duration::decode_body_with(&mut &encoded[..], &lenient).unwrap();
+            assert!((*v - decoded).abs() <= decoded.abs() * f64::EPSILON || *v == decoded);
+            assert!(Duration::decode_body(&mut &encoded[..]).is_err());
+        }
+    }
+
     #[test]
     fn test_text() {
         let test_pair = [
@@ -167,5 +203,136 @@ mod tests {
             date.encode_body(&mut buf).unwrap();
             assert_eq!(buf, n.to_be_bytes());
         }
+
+        // unlike other integers, dates are never shortened
+        assert!(DateUtc::decode_body(&mut &1i32.to_be_bytes()[..]).is_err());
+        assert!(DateUtc::decode_body(&mut &[0u8; 9][..]).is_err());
+    }
+
+    #[test]
+    fn test_empty_required_element() {
+        use crate::element::{DecodeOptions, Strictness};
+        let strict = DecodeOptions::with_strictness(Strictness::Strict);
+        let empty: &[u8] = &[];
+
+        assert_eq!(
+            TrackNumber::decode_body(&mut &*empty).unwrap(),
+            TrackNumber(0)
+        );
+        assert!(matches!(
+            TrackNumber::decode_body_with(&mut &*empty, &strict),
+            Err(crate::Error::EmptyRequiredElement(id)) if id == TrackNumber::ID
+        ));
+        assert_eq!(
+            TrackNumber::decode_body_with(&mut &[1u8][..], &strict).unwrap(),
+            TrackNumber(1)
+        );
+        // elements with a default value may be empty
+        assert_eq!(
+            FlagLacing::decode_body_with(&mut &*empty, &strict).unwrap(),
+            FlagLacing(1)
+        );
+
+        // the Cluster children are decoded by hand, and checked all the same
+        use crate::master::Cluster;
+        let cluster: &[u8] = &[0xE7, 0x80];
+        assert_eq!(*Cluster::decode_body(&mut &*cluster).unwrap().timestamp, 0);
+        assert!(matches!(
+            Cluster::decode_body_with(&mut &*cluster, &strict).unwrap_err().root(),
+            crate::Error::EmptyRequiredElement(id) if *id == Timestamp::ID
+        ));
+    }
+
+    #[test]
+    fn test_int_padded_beyond_8_bytes() {
+        use crate::element::{DecodeOptions, Strictness};
+        let lenient = DecodeOptions::with_strictness(Strictness::Lenient);
+
+        let padded = [0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x00];
+        assert!(DocTypeVersion::decode_body(&mut &padded[..]).is_err());
+        assert_eq!(
+            DocTypeVersion::decode_body_with(&mut &padded[..], &lenient).unwrap(),
+            DocTypeVersion(256)
+        );
+        // a non-zero excess byte would be truncated
+        let lossy = [1, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x00];
+        assert!(DocTypeVersion::decode_body_with(&mut &lossy[..], &lenient).is_err());
+
+        let negative = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xD6];
+        assert!(ReferenceBlock::decode_body(&mut &negative[..]).is_err());
+        assert_eq!(
+            ReferenceBlock::decode_body_with(&mut &negative[..], &lenient).unwrap(),
+            ReferenceBlock(-42)
+        );
+        // the extension has to match the sign of the low 8 bytes
+        let sign_flip = [0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+        assert!(ReferenceBlock::decode_body_with(&mut &sign_flip[..], &lenient).is_err());
     }
+
+    #[test]
+    fn test_sint_non_minimal() {
+        use crate::element::{DecodeOptions, Strictness};
+        let strict = DecodeOptions::with_strictness(Strictness::Strict);
+        let rejected = [
+            vec![0x00, 0x2A], // 42 with a leading 0x00
+            vec![0xFF, 0xD6], // -42 with a leading 0xFF
+            vec![0x00, 0x00],
+            vec![0xFF, 0xFF, 0x80],
+        ];
+        for encoded in rejected {
+            assert!(matches!(
+                ReferenceBlock::decode_body_with(&mut &*encoded, &strict),
+                Err(crate::Error::NonMinimalInteger(id)) if id == ReferenceBlock::ID
+            ));
+            // accepted by default
+            assert!(ReferenceBlock::decode_body(&mut &*encoded).is_ok());
+        }
+        let minimal = [
+            (vec![0x00, 0x80], 128),
+            (vec![0xFF, 0x7F], -129),
+            (vec![0x80], -128),
+            (vec![0x00], 0),
+        ];
+        for (encoded, value) in minimal {
+            assert_eq!(
+                ReferenceBlock::decode_body_with(&mut &*encoded, &strict).unwrap(),
+                ReferenceBlock(value)
+            );
+        }
+    }
+
+    /// Encode and decode each value with the given constructor, expecting the value back.
+    fn round_trip<T, V>(new: fn(V) -> T, values: Vec<V>)
+    where
+        T: Element + PartialEq + std::fmt::Debug,
+    {
+        for value in values {
+            let element = new(value);
+            let mut buf = vec![];
+            element.encode_body(&mut buf).unwrap();
+            assert_eq!(T::decode_body(&mut &buf[..]).unwrap(), element);
+        }
+    }
+
+    fn unsigned_samples() -> Vec<u64> {
+        vec![0, 1, 0x1234_5678, u64::MAX]
+    }
+    fn signed_samples() -> Vec<i64> {
+        vec![0, -1, 128, -129, 32768, 0x1234_5678, i64::MIN, i64::MAX]
+    }
+    fn float_samples() -> Vec<f64> {
+        vec![0.0, -2.5, std::f64::consts::PI, f64::MAX]
+    }
+    fn text_samples() -> Vec<String> {
+        vec![String::new(), "matroska".into(), "日本語".into()]
+    }
+    fn bin_samples() -> Vec<Bytes> {
+        vec![Bytes::new(), Bytes::from_static(&[0, 1, 0xFF])]
+    }
+    fn date_samples() -> Vec<i64> {
+        vec![0, -1, i64::MIN, i64::MAX]
+    }
+
+    // Round-trip tests for every generated element type.
+    include!(concat!(env!("OUT_DIR"), "/generated_tests.rs"));
 }
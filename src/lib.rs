@@ -2,15 +2,27 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+mod attachment; // Helpers for working with attachments.
+mod audio; // Helpers for working with audio settings.
 mod base; // base types for Matroska elements. ie. `VInt64`, `Header`, etc.
+mod chapter; // Helpers for working with chapters.
+mod cluster; // Helpers for working with clusters.
+mod cues; // Helpers for building Cues.
+mod ebml; // Helpers for working with the EBML header.
 mod element; // Element body definitions and traits.
 mod error;
+mod file; // A fully loaded Matroska file.
 mod frame;
 
 mod lacer;
 mod leaf; // Leaf elements in Matroska.
 mod master; // Master elements in Matroska.
+mod segment; // Helpers for working with segments.
 mod supplement; // Supplementary elements in Matroska. Void elements, CRC-32, etc.
+mod tags; // Helpers for working with tags.
+mod track; // Helpers for working with tracks.
+mod video; // Helpers for working with video settings.
+mod writer; // Streaming writers for incremental muxing.
 
 use bytes::*;
 use coding::*;
@@ -24,8 +36,14 @@ pub mod io;
 pub mod view;
 
 // Re-export common types
+pub use crate::audio::*;
+pub use crate::cues::*;
+pub use crate::file::*;
 pub use crate::frame::*;
 pub use crate::lacer::*;
+pub use crate::track::*;
+pub use crate::video::*;
+pub use crate::writer::*;
 pub use error::*;
 
 /// A prelude for common types and traits.
@@ -16,21 +16,39 @@ nested! {
 };
 */
 macro_rules! nested {
-    (required: [$($required:ident),*$(,)?], optional: [$($optional:ident),*$(,)?], multiple: [$($multiple:ident),*$(,)?],) => {
+    (required: [$($required:ident),*$(,)?], optional: [$($optional:ident),*$(,)?], multiple: [$($multiple:ident),*$(,)?], $(strict: $validate:ident,)?) => {
         paste::paste! {
             fn decode_body(buf: &mut dyn Buf) -> crate::Result<Self> {
-                let crc32 = if buf.remaining() > 6 && buf.chunk()[0] == 0xBF && buf.chunk()[1] == 0x84 {
-                    Some(Crc32::decode(buf)?)
-                } else {
-                    None
+                Self::decode_body_with(buf, &DecodeOptions::default())
+            }
+            fn decode_body_with(buf: &mut dyn Buf, options: &DecodeOptions) -> crate::Result<Self> {
+                let options = &options.nested(Self::ID)?;
+                let mut size_widths = SizeWidths::default();
+                let (crc32, checked) = decode_crc32(buf, Self::ID, options, &mut size_widths)?;
+                let mut checked_body;
+                let buf: &mut dyn Buf = match checked {
+                    Some(body) => {
+                        checked_body = body;
+                        &mut checked_body
+                    }
+                    None => buf,
                 };
 
                 $( let mut [<$required:snake>] = None;)*
                 $( let mut [<$optional:snake>] = None;)*
                 $( let mut [<$multiple:snake>] = Vec::new();)*
                 let mut void: Option<Void> = None;
+                let mut unknown = Vec::new();
 
+                let mut children = 0usize;
                 while let Ok(header) = Header::decode(buf) {
+                    children += 1;
+                    if children > options.max_children {
+                        return Err(Error::DecodeBudgetExceeded(Self::ID));
+                    }
+                    if options.preserve_size_width {
+                        size_widths.record(&header);
+                    }
                     if *header.size > buf.remaining() as u64 {
                         return Err(Error::try_get_error(*header.size as usize, buf.remaining()));
                     }
@@ -41,7 +59,7 @@ macro_rules! nested {
                                 return Err(Error::DuplicateElement { id: header.id, parent: Self::ID });
                             } else {
                                 let mut body = buf.take(body_size);
-                                [<$required:snake>] = Some($required::decode_body(&mut body)?);
+                                [<$required:snake>] = Some($required::decode_body_with(&mut body, options).map_err(|e| in_element(e, $required::ID, Self::ID))?);
                             }
                         } )*
                         $( $optional::ID => {
@@ -49,12 +67,12 @@ macro_rules! nested {
                                 return Err(Error::DuplicateElement { id: header.id, parent: Self::ID });
                             } else {
                                 let mut body = buf.take(body_size);
-                                [<$optional:snake>] = Some($optional::decode_body(&mut body)?);
+                                [<$optional:snake>] = Some($optional::decode_body_with(&mut body, options).map_err(|e| in_element(e, $optional::ID, Self::ID))?);
                             }
                         } )*
                         $( $multiple::ID => {
                             let mut body = buf.take(body_size);
-                            [<$multiple:snake>].push($multiple::decode_body(&mut body)?);
+                            [<$multiple:snake>].push($multiple::decode_body_with(&mut body, options).map_err(|e| in_element(e, $multiple::ID, Self::ID))?);
                         } )*
                         Void::ID => {
                             let mut body = buf.take(body_size);
@@ -66,9 +84,12 @@ macro_rules! nested {
                             }
                             log::info!("Skipping Void element in Element {}, size: {}B", Self::ID, *header.size);
                         }
+                        _ if options.preserve_unknown => {
+                            unknown.push(RawElement { header, body: buf.copy_to_bytes(body_size) });
+                        }
                         _ => {
                             buf.advance(*header.size as usize);
-                            log::warn!("Unknown element {}({}b) in Element({})", header.id, *header.size, Self::ID);
+                            skipped_unknown(&header, Self::ID, options);
                         }
                     }
                 }
@@ -77,36 +98,223 @@ macro_rules! nested {
                     return Err(Error::ShortRead);
                 }
 
-                Ok(Self {
+                let element = Self {
                     crc32,
                     $( [<$required:snake>]: [<$required:snake>].or(if $required::HAS_DEFAULT_VALUE { Some($required::default()) } else { None }).ok_or(Error::MissingElement($required::ID))?, )*
                     $( [<$optional:snake>], )*
                     $( [<$multiple:snake>], )*
                     void,
-                })
+                    unknown,
+                    size_widths,
+                };
+                // element level invariants, only enforced in strict mode
+                $( if options.is_strict() { element.$validate()?; } )?
+                Ok(element)
             }
             fn encode_body<B: BufMut>(&self, buf: &mut B) -> crate::Result<()> {
-                self.crc32.encode(buf)?;
+                if !self.size_widths.is_empty() {
+                    let mut children: Vec<&dyn Child> = vec![];
+                    children.extend(self.crc32.as_ref().map(|e| e as &dyn Child));
+                    $( children.push(&self.[<$required:snake>]); )*
+                    $( children.extend(self.[<$optional:snake>].as_ref().map(|e| e as &dyn Child)); )*
+                    $( children.extend(self.[<$multiple:snake>].iter().map(|e| e as &dyn Child)); )*
+                    children.extend(self.unknown.iter().map(|e| e as &dyn Child));
+                    return self.size_widths.write_children(&children, self.void.as_ref(), |bytes| {
+                        buf.put_slice(bytes);
+                        Ok(())
+                    });
+                }
+                if let Some(crc32) = &self.crc32 {
+                    crc32.encode(buf)?;
+                }
 
                 $( self.[<$required:snake>].encode(buf)?; )*
-                $( self.[<$optional:snake>].encode(buf)?; )*
-                $( self.[<$multiple:snake>].encode(buf)?; )*
+                $( if let Some(e) = &self.[<$optional:snake>] { e.encode(buf)?; } )*
+                $( for e in &self.[<$multiple:snake>] { e.encode(buf)?; } )*
+                self.unknown.encode(buf)?;
 
                 self.void.encode(buf)?;
 
                 Ok(())
             }
+            fn encode_into(&self, buf: &mut Vec<u8>) -> crate::Result<()> {
+                encode_master_into(self, buf)
+            }
+            fn encode_body_into(&self, buf: &mut Vec<u8>) -> crate::Result<()> {
+                // recorded size widths are only honored by the encoding through a buffer
+                if !self.size_widths.is_empty() {
+                    return self.encode_body(buf);
+                }
+                if let Some(crc32) = &self.crc32 {
+                    crc32.encode_into(buf)?;
+                }
+
+                $( self.[<$required:snake>].encode_into(buf)?; )*
+                $( if let Some(e) = &self.[<$optional:snake>] { e.encode_into(buf)?; } )*
+                $( for e in &self.[<$multiple:snake>] { e.encode_into(buf)?; } )*
+                self.unknown.encode(buf)?;
+
+                if let Some(void) = &self.void {
+                    void.encode_into(buf)?;
+                }
+
+                Ok(())
+            }
+            fn strip_padding(&mut self) {
+                self.crc32 = None;
+                self.void = None;
+
+                $( self.[<$required:snake>].strip_padding(); )*
+                $( self.[<$optional:snake>].iter_mut().for_each(Element::strip_padding); )*
+                $( self.[<$multiple:snake>].iter_mut().for_each(Element::strip_padding); )*
+            }
         }
     };
 }
 
+/// Report an unknown child element that was skipped, to the `on_unknown` hook if any.
+pub(crate) fn skipped_unknown(header: &Header, parent: VInt64, options: &DecodeOptions) {
+    match options.on_unknown {
+        Some(on_unknown) => on_unknown(header),
+        None => log::warn!(
+            "Unknown element {}({}b) in Element({})",
+            header.id,
+            *header.size,
+            parent
+        ),
+    }
+}
+
+/// Implement [`MasterElement`] for master elements, which all carry a `crc32` field.
+macro_rules! master_elements {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl MasterElement for $name {
+                fn crc32(&self) -> Option<&Crc32> {
+                    self.crc32.as_ref()
+                }
+                fn set_crc32(&mut self, crc32: Option<Crc32>) {
+                    self.crc32 = crc32;
+                }
+            }
+        )*
+    };
+}
+
+master_elements!(
+    Ebml,
+    Segment,
+    SeekHead,
+    Seek,
+    Info,
+    ChapterTranslate,
+    Cluster,
+    BlockGroup,
+    BlockAdditions,
+    BlockMore,
+    Tracks,
+    TrackEntry,
+    BlockAdditionMapping,
+    TrackTranslate,
+    Video,
+    Colour,
+    MasteringMetadata,
+    Projection,
+    Audio,
+    TrackOperation,
+    TrackCombinePlanes,
+    TrackPlane,
+    TrackJoinBlocks,
+    ContentEncodings,
+    ContentEncoding,
+    ContentCompression,
+    ContentEncryption,
+    ContentEncAesSettings,
+    Cues,
+    CuePoint,
+    CueTrackPositions,
+    CueReference,
+    Attachments,
+    AttachedFile,
+    Chapters,
+    EditionEntry,
+    EditionDisplay,
+    ChapterAtom,
+    ChapterTrack,
+    ChapterDisplay,
+    ChapProcess,
+    ChapProcessCommand,
+    Tags,
+    Tag,
+    Targets,
+    SimpleTag,
+);
+
+/// Wrap the error of a child element with the ID of its parent.
+///
+/// Buffer errors are first mapped to errors about the child, as for top-level elements.
+fn in_element(error: Error, child: VInt64, parent: VInt64) -> Error {
+    let error = match error {
+        Error::TryGetError(_) => Error::OverDecode(child),
+        Error::ShortRead => Error::UnderDecode(child),
+        e => e,
+    };
+    Error::InElement {
+        parent,
+        source: Box::new(error),
+    }
+}
+
+/// Decode the optional CRC-32 element at the start of a master element body.
+///
+/// In strict mode, the rest of the body is checked against the CRC-32 and returned,
+/// so the children can be decoded from it.
+///
+/// The CRC-32 is found by its header, whatever the width of its size, which is recorded in
+/// `size_widths` when preserving size widths.
+fn decode_crc32(
+    buf: &mut dyn Buf,
+    id: VInt64,
+    options: &DecodeOptions,
+    size_widths: &mut SizeWidths,
+) -> crate::Result<(Option<Crc32>, Option<Bytes>)> {
+    match Header::decode(&mut buf.chunk()) {
+        Ok(header) if header.id == Crc32::ID => {
+            if options.preserve_size_width {
+                size_widths.record(&header);
+            }
+        }
+        _ => return Ok((None, None)),
+    }
+    let crc32 = Crc32::decode(buf)?;
+    if !options.is_strict() {
+        return Ok((Some(crc32), None));
+    }
+    let body = buf.copy_to_bytes(buf.remaining());
+    let actual = Crc32::compute(&body);
+    if actual != crc32 {
+        return Err(Error::Crc32Mismatch {
+            id,
+            expected: *crc32,
+            actual: *actual,
+        });
+    }
+    Ok((Some(crc32), Some(body)))
+}
+
 /// EBML element, the first top-level element in a Matroska file.
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+///
+/// The `Default` value is a valid Matroska EBML header, with DocType "matroska".
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Ebml {
     /// Optional CRC-32 element for integrity checking.
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// EBMLVersion element, indicates the version of EBML used.
     pub ebml_version: Option<EbmlVersion>,
@@ -124,6 +332,24 @@ pub struct Ebml {
     pub doc_type_read_version: Option<DocTypeReadVersion>,
 }
 
+impl Default for Ebml {
+    fn default() -> Self {
+        Self {
+            crc32: None,
+            void: None,
+            unknown: Vec::new(),
+            size_widths: SizeWidths::default(),
+            ebml_version: Some(EbmlVersion(1)),
+            ebml_read_version: Some(EbmlReadVersion(1)),
+            ebml_max_id_length: EbmlMaxIdLength(4),
+            ebml_max_size_length: EbmlMaxSizeLength(8),
+            doc_type: Some(DocType("matroska".to_string())),
+            doc_type_version: Some(DocTypeVersion(4)),
+            doc_type_read_version: Some(DocTypeReadVersion(2)),
+        }
+    }
+}
+
 impl Element for Ebml {
     const ID: VInt64 = VInt64::from_encoded(0x1A45_DFA3);
     nested! {
@@ -134,12 +360,16 @@ impl Element for Ebml {
 }
 
 /// The Root Element that contains all other Top-Level Elements; see data-layout.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Segment {
     /// Optional CRC-32 element for integrity checking.
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains seeking information of Top-Level Elements; see data-layout.
     pub seek_head: Vec<SeekHead>,
@@ -168,6 +398,75 @@ impl Element for Segment {
     }
 }
 
+/// Builder for a [`Segment`], starting from its required [`Info`].
+///
+/// [`SegmentBuilder::build`] checks the result, so an authored Segment is valid before it is written.
+#[derive(Debug, Clone)]
+pub struct SegmentBuilder {
+    segment: Segment,
+}
+
+impl SegmentBuilder {
+    /// Start a Segment with the given `Info`, e.g. `Info::default()` with a title.
+    pub fn new(info: Info) -> Self {
+        Self {
+            segment: Segment {
+                info,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Add a track, after the existing ones.
+    pub fn add_track(&mut self, track: TrackEntry) -> &mut Self {
+        self.segment
+            .tracks
+            .get_or_insert_with(Default::default)
+            .track_entry
+            .push(track);
+        self
+    }
+
+    /// Add a Cluster, after the existing ones.
+    pub fn add_cluster(&mut self, cluster: Cluster) -> &mut Self {
+        self.segment.cluster.push(cluster);
+        self
+    }
+
+    /// Add a tag, after the existing ones, in a single `Tags` element.
+    pub fn add_tag(&mut self, tag: Tag) -> &mut Self {
+        if self.segment.tags.is_empty() {
+            self.segment.tags.push(Tags::default());
+        }
+        self.segment.tags[0].tag.push(tag);
+        self
+    }
+
+    /// Set the Cues, replacing any previous ones.
+    pub fn set_cues(&mut self, cues: Cues) -> &mut Self {
+        self.segment.cues = Some(cues);
+        self
+    }
+
+    /// Check and return the Segment.
+    ///
+    /// The `Info` must follow the Linked Segment rules, see [`Info::validate_linking`], each track
+    /// must pass [`TrackEntry::validate`] and [`TrackEntry::validate_type_consistency`], and the
+    /// blocks must only reference known tracks, see [`Segment::validate_track_references`].
+    ///
+    /// The builder is left as is, so more elements can be added to build another Segment.
+    pub fn build(&self) -> crate::Result<Segment> {
+        let segment = &self.segment;
+        segment.info.validate_linking()?;
+        for track in segment.tracks.iter().flat_map(|tracks| tracks.iter()) {
+            track.validate()?;
+            track.validate_type_consistency()?;
+        }
+        segment.validate_track_references()?;
+        Ok(segment.clone())
+    }
+}
+
 /// Contains seeking information of Top-Level Elements; see data-layout.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SeekHead {
@@ -175,6 +474,10 @@ pub struct SeekHead {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains a single seek entry to an EBML Element.
     pub seek: Vec<Seek>,
@@ -190,12 +493,16 @@ impl Element for SeekHead {
 }
 
 /// Contains a single seek entry to an EBML Element.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Seek {
     /// Optional CRC-32 element for integrity checking.
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The binary EBML ID of a Top-Level Element.
     pub seek_id: SeekId,
@@ -213,12 +520,18 @@ impl Element for Seek {
 }
 
 /// Contains general information about the Segment.
-#[derive(Debug, Clone, PartialEq, Default)]
+///
+/// The `Default` value identifies this crate as the muxing and writing application.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Info {
     /// Optional CRC-32 element for integrity checking.
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// A randomly generated unique ID to identify the Segment amongst many others (128 bits). It is equivalent to a UUID v4 \[@!RFC4122\] with all bits randomly (or pseudo-randomly) chosen. An actual UUID v4 value, where some bits are not random, **MAY** also be used. If the Segment is a part of a Linked Segment, then this Element is **REQUIRED**. The value of the unique ID **MUST** contain at least one bit set to 1.
     pub segment_uuid: Option<SegmentUuid>,
@@ -250,6 +563,32 @@ pub struct Info {
     pub writing_app: WritingApp,
 }
 
+impl Default for Info {
+    fn default() -> Self {
+        let app = concat!("mkv-element-", env!("CARGO_PKG_VERSION"));
+        Self {
+            crc32: None,
+            void: None,
+            unknown: Vec::new(),
+            size_widths: SizeWidths::default(),
+            segment_uuid: None,
+            segment_filename: None,
+            prev_uuid: None,
+            prev_filename: None,
+            next_uuid: None,
+            next_filename: None,
+            segment_family: Vec::new(),
+            chapter_translate: Vec::new(),
+            timestamp_scale: TimestampScale::default(),
+            duration: None,
+            date_utc: None,
+            title: None,
+            muxing_app: MuxingApp(app.to_string()),
+            writing_app: WritingApp(app.to_string()),
+        }
+    }
+}
+
 impl Element for Info {
     const ID: VInt64 = VInt64::from_encoded(0x1549A966);
     nested! {
@@ -266,6 +605,10 @@ pub struct ChapterTranslate {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The binary value used to represent this Segment in the chapter codec data. The format depends on the ChapProcessCodecID used; see [ChapProcessCodecID](https://www.matroska.org/technical/elements.html#chapprocesscodecid-element).
     pub chapter_translate_id: ChapterTranslateId,
@@ -293,6 +636,10 @@ pub struct Cluster {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Absolute timestamp of the cluster, expressed in Segment Ticks which is based on TimestampScale; see timestamp-ticks. This element **SHOULD** be the first child element of the Cluster it belongs to, or the second if that Cluster contains a CRC-32 element (crc-32).
     pub timestamp: Timestamp,
@@ -308,10 +655,19 @@ pub struct Cluster {
 impl Element for Cluster {
     const ID: VInt64 = VInt64::from_encoded(0x1F43B675);
     fn decode_body(buf: &mut dyn Buf) -> crate::Result<Self> {
-        let crc32 = if buf.remaining() > 6 && buf.chunk()[0] == 0xBF && buf.chunk()[1] == 0x84 {
-            Some(Crc32::decode(buf)?)
-        } else {
-            None
+        Self::decode_body_with(buf, &DecodeOptions::default())
+    }
+    fn decode_body_with(buf: &mut dyn Buf, options: &DecodeOptions) -> crate::Result<Self> {
+        let options = &options.nested(Self::ID)?;
+        let mut size_widths = SizeWidths::default();
+        let (crc32, checked) = decode_crc32(buf, Self::ID, options, &mut size_widths)?;
+        let mut checked_body;
+        let buf: &mut dyn Buf = match checked {
+            Some(body) => {
+                checked_body = body;
+                &mut checked_body
+            }
+            None => buf,
         };
 
         let mut timestamp = None;
@@ -320,8 +676,17 @@ impl Element for Cluster {
         let mut blocks = Vec::new();
 
         let mut void: Option<Void> = None;
+        let mut unknown = Vec::new();
 
+        let mut children = 0usize;
         while let Ok(header) = Header::decode(buf) {
+            children += 1;
+            if children > options.max_children {
+                return Err(Error::DecodeBudgetExceeded(Self::ID));
+            }
+            if options.preserve_size_width {
+                size_widths.record(&header);
+            }
             if *header.size > buf.remaining() as u64 {
                 return Err(Error::OverDecode(header.id));
             }
@@ -335,7 +700,10 @@ impl Element for Cluster {
                         });
                     } else {
                         let mut body = buf.take(body_size);
-                        timestamp = Some(Timestamp::decode_body(&mut body)?);
+                        timestamp = Some(
+                            Timestamp::decode_body_with(&mut body, options)
+                                .map_err(|e| in_element(e, Timestamp::ID, Self::ID))?,
+                        );
                     }
                 }
                 Position::ID => {
@@ -346,7 +714,10 @@ impl Element for Cluster {
                         });
                     } else {
                         let mut body = buf.take(body_size);
-                        position = Some(Position::decode_body(&mut body)?);
+                        position = Some(
+                            Position::decode_body_with(&mut body, options)
+                                .map_err(|e| in_element(e, Position::ID, Self::ID))?,
+                        );
                     }
                 }
                 PrevSize::ID => {
@@ -357,16 +728,27 @@ impl Element for Cluster {
                         });
                     } else {
                         let mut body = buf.take(body_size);
-                        prev_size = Some(PrevSize::decode_body(&mut body)?);
+                        prev_size = Some(
+                            PrevSize::decode_body_with(&mut body, options)
+                                .map_err(|e| in_element(e, PrevSize::ID, Self::ID))?,
+                        );
                     }
                 }
                 SimpleBlock::ID => {
                     let mut body = buf.take(body_size);
-                    blocks.push(SimpleBlock::decode_body(&mut body)?.into());
+                    blocks.push(
+                        SimpleBlock::decode_body_with(&mut body, options)
+                            .map_err(|e| in_element(e, SimpleBlock::ID, Self::ID))?
+                            .into(),
+                    );
                 }
                 BlockGroup::ID => {
                     let mut body = buf.take(body_size);
-                    blocks.push(BlockGroup::decode_body(&mut body)?.into());
+                    blocks.push(
+                        BlockGroup::decode_body_with(&mut body, options)
+                            .map_err(|e| in_element(e, BlockGroup::ID, Self::ID))?
+                            .into(),
+                    );
                 }
                 Void::ID => {
                     let mut body = buf.take(body_size);
@@ -384,14 +766,15 @@ impl Element for Cluster {
                         *header.size
                     );
                 }
+                _ if options.preserve_unknown => {
+                    unknown.push(RawElement {
+                        header,
+                        body: buf.copy_to_bytes(body_size),
+                    });
+                }
                 _ => {
                     buf.advance(*header.size as usize);
-                    log::warn!(
-                        "Unknown element {}({}b) in Element({})",
-                        header.id,
-                        *header.size,
-                        Self::ID
-                    );
+                    skipped_unknown(&header, Self::ID, options);
                 }
             }
         }
@@ -407,19 +790,85 @@ impl Element for Cluster {
             prev_size,
             blocks,
             void,
+            unknown,
+            size_widths,
         })
     }
 
     fn encode_body<B: BufMut>(&self, buf: &mut B) -> crate::Result<()> {
-        self.crc32.encode(buf)?;
+        if !self.size_widths.is_empty() {
+            let mut children: Vec<&dyn Child> = vec![];
+            children.extend(self.crc32.as_ref().map(|e| e as &dyn Child));
+            children.push(&self.timestamp);
+            children.extend(self.position.as_ref().map(|e| e as &dyn Child));
+            children.extend(self.prev_size.as_ref().map(|e| e as &dyn Child));
+            children.extend(self.blocks.iter().map(|e| e as &dyn Child));
+            children.extend(self.unknown.iter().map(|e| e as &dyn Child));
+            return self
+                .size_widths
+                .write_children(&children, self.void.as_ref(), |bytes| {
+                    buf.put_slice(bytes);
+                    Ok(())
+                });
+        }
+        if let Some(crc32) = &self.crc32 {
+            crc32.encode(buf)?;
+        }
         self.timestamp.encode(buf)?;
-        self.position.encode(buf)?;
-        self.prev_size.encode(buf)?;
+        if let Some(position) = &self.position {
+            position.encode(buf)?;
+        }
+        if let Some(prev_size) = &self.prev_size {
+            prev_size.encode(buf)?;
+        }
         self.blocks.encode(buf)?;
+        self.unknown.encode(buf)?;
 
         self.void.encode(buf)?;
         Ok(())
     }
+
+    fn encode_into(&self, buf: &mut Vec<u8>) -> crate::Result<()> {
+        encode_master_into(self, buf)
+    }
+
+    fn encode_body_into(&self, buf: &mut Vec<u8>) -> crate::Result<()> {
+        if !self.size_widths.is_empty() {
+            return self.encode_body(buf);
+        }
+        if let Some(crc32) = &self.crc32 {
+            crc32.encode_into(buf)?;
+        }
+        self.timestamp.encode_into(buf)?;
+        if let Some(position) = &self.position {
+            position.encode_into(buf)?;
+        }
+        if let Some(prev_size) = &self.prev_size {
+            prev_size.encode_into(buf)?;
+        }
+        for block in &self.blocks {
+            match block {
+                ClusterBlock::Simple(b) => b.encode_into(buf)?,
+                ClusterBlock::Group(b) => b.encode_into(buf)?,
+            }
+        }
+        self.unknown.encode(buf)?;
+
+        if let Some(void) = &self.void {
+            void.encode_into(buf)?;
+        }
+        Ok(())
+    }
+
+    fn strip_padding(&mut self) {
+        self.crc32 = None;
+        self.void = None;
+        for block in &mut self.blocks {
+            if let ClusterBlock::Group(g) = block {
+                g.strip_padding();
+            }
+        }
+    }
 }
 
 /// Basic container of information containing a single Block and information specific to that Block.
@@ -429,6 +878,10 @@ pub struct BlockGroup {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Block containing the actual data to be rendered and a timestamp relative to the Cluster Timestamp; see [basics](https://www.matroska.org/technical/basics.html#block-structure) on Block Structure.
     pub block: Block,
@@ -464,6 +917,10 @@ pub struct BlockAdditions {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contain the BlockAdditional and some parameters.
     pub block_more: Vec<BlockMore>,
@@ -485,6 +942,10 @@ pub struct BlockMore {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Interpreted by the codec as it wishes (using the BlockAddID).
     pub block_additional: BlockAdditional,
@@ -508,6 +969,10 @@ pub struct Tracks {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Describes a track with all Elements.
     pub track_entry: Vec<TrackEntry>,
@@ -529,6 +994,10 @@ pub struct TrackEntry {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The track number as used in the Block Header.
     pub track_number: TrackNumber,
@@ -619,6 +1088,10 @@ pub struct BlockAdditionMapping {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// If the track format extension needs content beside frames, the value refers to the BlockAddID (BlockAddID), value being described. To keep MaxBlockAdditionID as low as possible, small values **SHOULD** be used.
     pub block_add_id_value: Option<BlockAddIdValue>,
@@ -645,6 +1118,10 @@ pub struct TrackTranslate {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The binary value used to represent this `TrackEntry` in the chapter codec data. The format depends on the `ChapProcessCodecID` used; see ChapProcessCodecID.
     pub track_translate_track_id: TrackTranslateTrackId,
@@ -672,6 +1149,10 @@ pub struct Video {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Specify whether the video frames in this track are interlaced.
     /// * 0 - undetermined,
@@ -756,6 +1237,10 @@ pub struct Colour {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The Matrix Coefficients of the video used to derive luma and chroma values from red, green, and blue color primaries. For clarity, the value and meanings for MatrixCoefficients are adopted from Table 4 of ISO/IEC 23001-8:2016 or ITU-T H.273.
     /// * 0 - Identity,
@@ -864,6 +1349,10 @@ pub struct MasteringMetadata {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Red X chromaticity coordinate, as defined by \[@!CIE-1931\].
     pub primary_r_chromaticity_x: Option<PrimaryRChromaticityX>,
@@ -907,6 +1396,10 @@ pub struct Projection {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Describes the projection used for this video track.
     /// * 0 - rectangular,
@@ -940,6 +1433,10 @@ pub struct Audio {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Sampling frequency in Hz.
     pub sampling_frequency: SamplingFrequency,
@@ -982,6 +1479,10 @@ pub struct TrackOperation {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains the list of all video plane tracks that need to be combined to create this 3D track
     pub track_combine_planes: Option<TrackCombinePlanes>,
@@ -1005,6 +1506,10 @@ pub struct TrackCombinePlanes {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains a video plane track that need to be combined to create this 3D track
     pub track_plane: Vec<TrackPlane>,
@@ -1026,6 +1531,10 @@ pub struct TrackPlane {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The trackUID number of the track representing the plane.
     pub track_plane_uid: TrackPlaneUid,
@@ -1052,6 +1561,10 @@ pub struct TrackJoinBlocks {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The trackUID number of a track whose blocks are used to create this virtual track.
     pub track_join_uid: Vec<TrackJoinUid>,
@@ -1073,6 +1586,10 @@ pub struct ContentEncodings {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Settings for one content encoding like compression or encryption.
     pub content_encoding: Vec<ContentEncoding>,
@@ -1094,6 +1611,10 @@ pub struct ContentEncoding {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Tell in which order to apply each `ContentEncoding` of the `ContentEncodings`. The decoder/demuxer **MUST** start with the `ContentEncoding` with the highest `ContentEncodingOrder` and work its way down to the `ContentEncoding` with the lowest `ContentEncodingOrder`. This value **MUST** be unique over for each `ContentEncoding` found in the `ContentEncodings` of this `TrackEntry`.
     pub content_encoding_order: ContentEncodingOrder,
@@ -1128,6 +1649,10 @@ pub struct ContentCompression {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The compression algorithm used. Compression method "1" (bzlib) and "2" (lzo1x) are lacking proper documentation on the format which limits implementation possibilities. Due to licensing conflicts on commonly available libraries compression methods "2" (lzo1x) does not offer widespread interoperability. A Matroska Writer **SHOULD NOT** use these compression methods by default. A Matroska Reader **MAY** support methods "1" and "2" as possible, and **SHOULD** support other methods.
     /// * 0 - zlib,
@@ -1155,6 +1680,10 @@ pub struct ContentEncryption {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The encryption algorithm used.
     /// * 0 - Not encrypted,
@@ -1185,6 +1714,10 @@ pub struct ContentEncAesSettings {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The AES cipher mode used in the encryption.
     /// * 1 - AES-CTR,
@@ -1208,6 +1741,10 @@ pub struct Cues {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains all information relative to a seek point in the Segment.
     pub cue_point: Vec<CuePoint>,
@@ -1229,6 +1766,10 @@ pub struct CuePoint {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Absolute timestamp of the seek point, expressed in Matroska Ticks -- i.e., in nanoseconds; see timestamp-ticks.
     pub cue_time: CueTime,
@@ -1252,6 +1793,10 @@ pub struct CueTrackPositions {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The track for which a position is given.
     pub cue_track: CueTrack,
@@ -1285,6 +1830,10 @@ pub struct CueReference {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Timestamp of the referenced Block, expressed in Matroska Ticks -- i.e., in nanoseconds; see timestamp-ticks.
     pub cue_ref_time: CueRefTime,
@@ -1306,6 +1855,10 @@ pub struct Attachments {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// An attached file.
     pub attached_file: Vec<AttachedFile>,
@@ -1326,6 +1879,10 @@ pub struct AttachedFile {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// A human-friendly name for the attached file.
     pub file_description: Option<FileDescription>,
@@ -1354,6 +1911,10 @@ pub struct Chapters {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains all information about a Segment edition.
     pub edition_entry: Vec<EditionEntry>,
@@ -1375,6 +1936,10 @@ pub struct EditionEntry {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// A unique ID to identify the edition. It's useful for tagging an edition.
     pub edition_uid: Option<EditionUid>,
@@ -1406,6 +1971,10 @@ pub struct EditionDisplay {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains the string to use as the edition name.
     pub edition_string: EditionString,
@@ -1429,6 +1998,10 @@ pub struct ChapterAtom {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains the atom information to use as the chapter atom (apply to all tracks).
     pub chapter_uid: ChapterUid,
@@ -1484,6 +2057,10 @@ pub struct ChapterTrack {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// UID of the Track to apply this chapter to. In the absence of a control track, choosing this chapter will select the listed Tracks and deselect unlisted tracks. Absence of this Element indicates that the Chapter **SHOULD** be applied to any currently used Tracks.
     pub chapter_track_uid: Vec<ChapterTrackUid>,
@@ -1504,6 +2081,10 @@ pub struct ChapterDisplay {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains the string to use as the chapter atom.
     pub chap_string: ChapString,
@@ -1531,6 +2112,10 @@ pub struct ChapProcess {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Contains the type of the codec used for the processing. A value of 0 means native Matroska processing (to be defined), a value of 1 means the DVD command set is used; see menu-features on DVD menus. More codec IDs can be added later.
     pub chap_process_codec_id: ChapProcessCodecId,
@@ -1556,6 +2141,10 @@ pub struct ChapProcessCommand {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Defines when the process command **SHOULD** be handled
     /// * 0 - during the whole chapter,
@@ -1582,6 +2171,10 @@ pub struct Tags {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// A single metadata descriptor.
     pub tag: Vec<Tag>,
@@ -1603,6 +2196,10 @@ pub struct Tag {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// Specifies which other elements the metadata represented by the Tag applies to. If empty or omitted, then the Tag describes everything in the Segment.
     pub targets: Targets,
@@ -1626,6 +2223,10 @@ pub struct Targets {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// A number to indicate the logical level of the target.
     /// * 70 - COLLECTION,
@@ -1685,6 +2286,10 @@ pub struct SimpleTag {
     pub crc32: Option<Crc32>,
     /// void element, useful for reserving space during writing.
     pub void: Option<Void>,
+    /// Unknown child elements, only kept when decoding with `preserve_unknown` set in [`DecodeOptions`].
+    pub unknown: Vec<RawElement>,
+    /// Widths of the encoded sizes of the children, only kept when decoding with `preserve_size_width` set in [`DecodeOptions`].
+    pub size_widths: SizeWidths,
 
     /// The name of the Tag that is going to be stored.
     pub tag_name: TagName,
@@ -1708,5 +2313,6 @@ impl Element for SimpleTag {
       required: [ TagName, TagLanguage, TagDefault ],
       optional: [ TagLanguageBcp47, TagString, TagBinary ],
       multiple: [ SimpleTag ],
+      strict: validate,
     }
 }
@@ -0,0 +1,652 @@
+//! Helpers for working with segments.
+
+use crate::base::{Header, VInt64};
+use crate::element::Element;
+use crate::io::blocking_impl::WriteElement;
+use crate::leaf::{SeekId, SeekPosition};
+use crate::master::{Info, Seek, SeekHead, Segment, TrackEntry};
+use crate::supplement::Child;
+
+impl SeekHead {
+    /// Iterate over the Seek entries, see also `&SeekHead` which is iterable.
+    pub fn iter(&self) -> std::slice::Iter<'_, Seek> {
+        self.seek.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SeekHead {
+    type Item = &'a Seek;
+    type IntoIter = std::slice::Iter<'a, Seek>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.seek.iter()
+    }
+}
+
+impl SeekId {
+    /// The ID of the element this Seek points to, decoded from the `SeekID` bytes.
+    pub fn target_id(&self) -> crate::Result<VInt64> {
+        use crate::Decode;
+        let buf = &mut &self[..];
+        let id = VInt64::decode(buf)?;
+        if !buf.is_empty() {
+            return Err(crate::Error::OverDecode(Self::ID));
+        }
+        Ok(id)
+    }
+}
+
+impl SeekPosition {
+    /// The absolute position in the file, given the position where the Segment data starts,
+    /// i.e. right after the Segment header.
+    pub fn to_absolute(&self, segment_data_position: u64) -> u64 {
+        segment_data_position + **self
+    }
+
+    /// The Segment Position of the absolute `position`, see [`SeekPosition::to_absolute`].
+    ///
+    /// Returns [`crate::Error::InvalidSeekPosition`] if the position is before the Segment data.
+    pub fn from_absolute(position: u64, segment_data_position: u64) -> crate::Result<Self> {
+        position
+            .checked_sub(segment_data_position)
+            .map(SeekPosition)
+            .ok_or(crate::Error::InvalidSeekPosition(position))
+    }
+}
+
+impl Info {
+    /// Check the Linked Segment rules: `PrevUUID` and `NextUUID` must differ from the
+    /// `SegmentUUID`, and a `ChapterTranslate` requires at least one `SegmentFamily`.
+    pub fn validate_linking(&self) -> crate::Result<()> {
+        let invalid = |detail: &str| {
+            Err(crate::Error::InvalidLinking {
+                detail: detail.to_string(),
+            })
+        };
+        if let Some(uuid) = &self.segment_uuid {
+            if self.prev_uuid.as_ref().is_some_and(|prev| **prev == **uuid) {
+                return invalid("PrevUUID is equal to SegmentUUID");
+            }
+            if self.next_uuid.as_ref().is_some_and(|next| **next == **uuid) {
+                return invalid("NextUUID is equal to SegmentUUID");
+            }
+        }
+        if !self.chapter_translate.is_empty() && self.segment_family.is_empty() {
+            return invalid("ChapterTranslate requires a SegmentFamily");
+        }
+        Ok(())
+    }
+}
+
+impl Segment {
+    /// Duration of the Segment in nanoseconds, computed from the frames of the last Cluster.
+    ///
+    /// This is the end time of the last frame, using [`crate::Frame::effective_duration`], and is useful
+    /// when `Info.duration` is absent, as in live captures.
+    /// Returns `None` if the last Cluster has no frames, or if the end does not fit in nanoseconds.
+    pub fn computed_duration_ns(&self) -> Option<i64> {
+        let timestamp_scale = *self.info.timestamp_scale;
+        let cluster = self.cluster.last()?;
+        let track = |number: u64| self.tracks.as_ref()?.by_number(number);
+        let mut end = None;
+        for frame in cluster.frames().filter_map(|frame| frame.ok()) {
+            let duration = frame
+                .effective_duration(track(frame.track_number), timestamp_scale)
+                .unwrap_or(0);
+            let frame_end = frame.timestamp.checked_add(i64::try_from(duration).ok()?)?;
+            end = end.max(Some(frame_end));
+        }
+        crate::Ticks(end?).to_ns(timestamp_scale).ok()
+    }
+
+    /// Write the Segment with an unknown size, as in live streams where the size is not known upfront.
+    pub fn write_unknown_size<W: std::io::Write + ?Sized>(&self, w: &mut W) -> crate::Result<()> {
+        self.write_element(&Header::new(Self::ID, VInt64::new_unknown()), w)
+    }
+
+    /// Write the Segment one top-level child at a time, instead of building the whole body in memory.
+    ///
+    /// The size is reserved and patched with a [`crate::SeekableWriter`] once all children
+    /// are written, so that an interrupted write still leaves a readable live-style Segment.
+    /// The children are written in the same order and with the same size widths as
+    /// [`crate::io::blocking_impl::WriteTo::write_to`].
+    pub fn write_streaming<W: std::io::Write + std::io::Seek + ?Sized>(
+        &self,
+        w: &mut W,
+    ) -> crate::Result<()> {
+        let mut w = crate::SeekableWriter::new(w);
+        let size_position = w.start_element(Self::ID)?;
+
+        let mut children: Vec<&dyn Child> = vec![];
+        children.extend(self.crc32.as_ref().map(|e| e as &dyn Child));
+        children.push(&self.info);
+        children.extend(self.tracks.as_ref().map(|e| e as &dyn Child));
+        children.extend(self.cues.as_ref().map(|e| e as &dyn Child));
+        children.extend(self.attachments.as_ref().map(|e| e as &dyn Child));
+        children.extend(self.chapters.as_ref().map(|e| e as &dyn Child));
+        children.extend(self.seek_head.iter().map(|e| e as &dyn Child));
+        children.extend(self.tags.iter().map(|e| e as &dyn Child));
+        children.extend(self.cluster.iter().map(|e| e as &dyn Child));
+        children.extend(self.unknown.iter().map(|e| e as &dyn Child));
+        // each child is encoded on its own, honoring the recorded layout
+        self.size_widths
+            .write_children(&children, self.void.as_ref(), |bytes| {
+                Ok(std::io::Write::write_all(&mut w, bytes)?)
+            })?;
+
+        w.patch_size(size_position)?;
+        Ok(())
+    }
+
+    /// Tracks of the Segment with the given `CodecID`, e.g. "A_OPUS".
+    pub fn tracks_with_codec(&self, codec_id: &str) -> Vec<&TrackEntry> {
+        self.tracks
+            .iter()
+            .flat_map(|tracks| tracks.iter())
+            .filter(|entry| &*entry.codec_id == codec_id)
+            .collect()
+    }
+
+    /// Distinct `CodecID`s of the tracks of the Segment, in track order.
+    pub fn codec_ids(&self) -> Vec<&str> {
+        let mut codec_ids: Vec<&str> = vec![];
+        for entry in self.tracks.iter().flat_map(|t| t.iter()) {
+            if !codec_ids.contains(&&*entry.codec_id) {
+                codec_ids.push(&entry.codec_id);
+            }
+        }
+        codec_ids
+    }
+
+    /// Check that every block of the Segment belongs to a track described in its Tracks.
+    ///
+    /// Only the block headers are read. Returns [`Error::UnknownTrackReference`](crate::Error::UnknownTrackReference)
+    /// with the first track number that has no `TrackEntry`, which usually points to a muxing bug.
+    pub fn validate_track_references(&self) -> crate::Result<()> {
+        let tracks = self.tracks.as_ref();
+        for block in self.cluster.iter().flat_map(|c| c.blocks.iter()) {
+            let (track_number, _) = block.header()?;
+            if tracks.and_then(|t| t.by_number(track_number)).is_none() {
+                return Err(crate::Error::UnknownTrackReference(track_number));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the Segment against the restrictions of the `webm` doc type.
+    ///
+    /// For now, this rejects chapters using the DVD command set (`ChapProcessCodecID` 1),
+    /// as WebM does not support DVD menus.
+    pub fn validate_webm(&self) -> crate::Result<()> {
+        let mut editions = self.chapters.iter().flat_map(|chapters| chapters.iter());
+        if let Some(edition) = editions.find(|e| e.has_dvd_process()) {
+            return Err(crate::Error::InvalidWebm {
+                detail: format!(
+                    "edition {} uses the DVD command set",
+                    edition.edition_uid.as_ref().map_or(0, |uid| **uid)
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Append the Clusters and Tags of `other` to this Segment, to join two files.
+    ///
+    /// When `rebase` is set, the appended Clusters are shifted by the duration of this Segment,
+    /// taken from `Info.duration` rounded to the nearest tick, or else from
+    /// [`Segment::computed_duration_ns`].
+    /// Both Segments are expected to describe the same tracks; a mismatch is only logged.
+    ///
+    /// Returns [`crate::Error::TimestampScaleMismatch`] if the Segments have different
+    /// `TimestampScale`s, as the timestamps of the appended blocks would be off.
+    pub fn append(&mut self, other: &Segment, rebase: bool) -> crate::Result<()> {
+        let timestamp_scale = *self.info.timestamp_scale;
+        if *other.info.timestamp_scale != timestamp_scale {
+            return Err(crate::Error::TimestampScaleMismatch {
+                expected: timestamp_scale,
+                found: *other.info.timestamp_scale,
+            });
+        }
+        let track_numbers = |segment: &Segment| {
+            let mut numbers: Vec<u64> = segment
+                .tracks
+                .iter()
+                .flat_map(|tracks| tracks.iter())
+                .map(|entry| *entry.track_number)
+                .collect();
+            numbers.sort_unstable();
+            numbers
+        };
+        if track_numbers(self) != track_numbers(other) {
+            log::warn!("appending a Segment with different track numbers");
+        }
+
+        let delta = if rebase {
+            match self.info.duration {
+                Some(duration) => duration.round() as i64,
+                None => match self.computed_duration_ns() {
+                    Some(ns) => *crate::Ticks::from_ns(ns, timestamp_scale)?,
+                    None => 0,
+                },
+            }
+        } else {
+            0
+        };
+        for cluster in &other.cluster {
+            let mut cluster = cluster.clone();
+            cluster.shift_timestamp(delta)?;
+            self.cluster.push(cluster);
+        }
+        self.tags.extend(other.tags.iter().cloned());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::leaf::*;
+    use crate::master::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_computed_duration() {
+        let mut segment = Segment {
+            info: Info {
+                timestamp_scale: TimestampScale(1_000_000),
+                ..Default::default()
+            },
+            tracks: Some(Tracks {
+                track_entry: vec![TrackEntry {
+                    track_number: TrackNumber(1),
+                    default_duration: Some(DefaultDuration(40_000_000)),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            cluster: vec![
+                Cluster {
+                    timestamp: Timestamp(0),
+                    blocks: vec![SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x00, 0x80])).into()],
+                    ..Default::default()
+                },
+                Cluster {
+                    timestamp: Timestamp(1000),
+                    blocks: vec![
+                        // track 1 at 1040, using the DefaultDuration of 40ms
+                        SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x28, 0x80, 1])).into(),
+                        // track 2 at 1000, with a BlockDuration of 60ms
+                        BlockGroup {
+                            block: Block(Bytes::from_static(&[0x82, 0x00, 0x00, 0x00, 2])),
+                            block_duration: Some(BlockDuration(60)),
+                            ..Default::default()
+                        }
+                        .into(),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(segment.info.duration, None);
+        assert_eq!(segment.computed_duration_ns(), Some(1_080_000_000));
+
+        segment.cluster.clear();
+        assert_eq!(segment.computed_duration_ns(), None);
+    }
+
+    #[test]
+    fn test_computed_duration_overflow() {
+        let mut segment = Segment {
+            info: Info {
+                timestamp_scale: TimestampScale(1),
+                ..Default::default()
+            },
+            tracks: Some(Tracks {
+                track_entry: vec![TrackEntry {
+                    track_number: TrackNumber(1),
+                    default_duration: Some(DefaultDuration(u64::MAX / 2)),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            cluster: vec![Cluster {
+                timestamp: Timestamp(0),
+                // 3 Xiph-laced frames at 10, whose DefaultDuration sum overflows
+                blocks: vec![
+                    SimpleBlock(Bytes::from_static(&[
+                        0x81, 0x00, 0x0A, 0x82, 2, 1, 1, 0xA, 0xB, 0xC,
+                    ]))
+                    .into(),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let frame = segment.cluster[0].frames().next().unwrap().unwrap();
+        let track = segment.tracks.as_ref().unwrap().by_number(1);
+        assert_eq!(frame.effective_duration(track, 1), None);
+        assert_eq!(segment.computed_duration_ns(), Some(10));
+
+        // a single frame lasting u64::MAX / 2 ticks does not fit after its timestamp
+        segment.cluster[0].blocks =
+            vec![SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x0A, 0x80, 1])).into()];
+        assert_eq!(segment.computed_duration_ns(), None);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut segment = Segment {
+            info: Info {
+                timestamp_scale: TimestampScale(1_000_000),
+                ..Default::default()
+            },
+            cluster: vec![Cluster {
+                timestamp: Timestamp(0),
+                blocks: vec![
+                    BlockGroup {
+                        block: Block(Bytes::from_static(&[0x81, 0x00, 0x00, 0x80, 1])),
+                        block_duration: Some(BlockDuration(500)),
+                        ..Default::default()
+                    }
+                    .into(),
+                ],
+                ..Default::default()
+            }],
+            tags: vec![Tags::default()],
+            ..Default::default()
+        };
+        let other = segment.clone();
+
+        segment.append(&other, true).unwrap();
+        assert_eq!(segment.cluster.len(), 2);
+        assert_eq!(*segment.cluster[1].timestamp, 500);
+        assert_eq!(segment.tags.len(), 2);
+
+        segment.info.duration = Some(Duration(2000.0));
+        segment.append(&other, true).unwrap();
+        assert_eq!(*segment.cluster[2].timestamp, 2000);
+
+        segment.append(&other, false).unwrap();
+        assert_eq!(segment.cluster.len(), 4);
+        assert_eq!(*segment.cluster[3].timestamp, 0);
+
+        // a fractional duration is rounded to the nearest tick
+        segment.info.duration = Some(Duration(2999.6));
+        segment.append(&other, true).unwrap();
+        assert_eq!(*segment.cluster[4].timestamp, 3000);
+
+        let mut other = other;
+        other.info.timestamp_scale = TimestampScale(1_000);
+        assert!(matches!(
+            segment.append(&other, true),
+            Err(crate::Error::TimestampScaleMismatch {
+                expected: 1_000_000,
+                found: 1_000
+            })
+        ));
+        assert_eq!(segment.cluster.len(), 5);
+    }
+
+    #[test]
+    fn test_codecs() {
+        let track = |n: u64, codec: &str| TrackEntry {
+            track_number: TrackNumber(n),
+            codec_id: CodecId(codec.to_string()),
+            ..Default::default()
+        };
+        let segment = Segment {
+            tracks: Some(Tracks {
+                track_entry: vec![
+                    track(1, "V_VP9"),
+                    track(2, "A_OPUS"),
+                    track(3, "A_OPUS"),
+                    track(4, "S_TEXT/UTF8"),
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(segment.codec_ids(), vec!["V_VP9", "A_OPUS", "S_TEXT/UTF8"]);
+        let opus = segment.tracks_with_codec("A_OPUS");
+        assert_eq!(
+            opus.iter().map(|t| *t.track_number).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(segment.tracks_with_codec("A_AAC").is_empty());
+        assert!(Segment::default().codec_ids().is_empty());
+    }
+
+    #[test]
+    fn test_validate_track_references() {
+        let block = |track: u8| SimpleBlock(vec![0x80 | track, 0x00, 0x00, 0x80, 0xAA].into());
+        let mut segment = Segment {
+            tracks: Some(Tracks {
+                track_entry: vec![TrackEntry {
+                    track_number: TrackNumber(1),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            cluster: vec![Cluster {
+                blocks: vec![block(1).into(), block(1).into()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(segment.validate_track_references().is_ok());
+
+        segment.cluster[0].push_block(block(3));
+        assert!(matches!(
+            segment.validate_track_references(),
+            Err(crate::Error::UnknownTrackReference(3))
+        ));
+    }
+
+    #[test]
+    fn test_write_unknown_size() {
+        use crate::base::Header;
+        use crate::element::Element;
+        use crate::io::blocking_impl::ReadFrom;
+
+        let mut buf = vec![];
+        Segment::default().write_unknown_size(&mut buf).unwrap();
+        let reader = &mut &buf[..];
+        let header = Header::read_from(reader).unwrap();
+        assert_eq!(header.id, Segment::ID);
+        assert!(header.size.is_unknown);
+        // the body follows the header as usual
+        assert_eq!(Segment::decode_body(reader).unwrap(), Segment::default());
+    }
+
+    #[test]
+    fn test_write_streaming() {
+        use crate::base::{Header, VInt64};
+        use crate::element::Element;
+        use crate::io::blocking_impl::WriteElement;
+
+        let segment = Segment {
+            info: Info {
+                title: Some(Title("streamed".to_string())),
+                ..Default::default()
+            },
+            tracks: Some(Tracks {
+                track_entry: vec![TrackEntry {
+                    track_number: TrackNumber(1),
+                    codec_id: CodecId("A_OPUS".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            cluster: vec![
+                Cluster {
+                    timestamp: Timestamp(0),
+                    blocks: vec![
+                        SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x00, 0x80, 1])).into()
+                    ],
+                    ..Default::default()
+                };
+                3
+            ],
+            tags: vec![Tags::default()],
+            ..Default::default()
+        };
+
+        let mut streamed = std::io::Cursor::new(vec![0xAA; 4]);
+        streamed.set_position(4);
+        segment.write_streaming(&mut streamed).unwrap();
+        let streamed = streamed.into_inner();
+
+        // the in-memory encoding, with the same 8-byte size width
+        let mut body = vec![];
+        segment.encode_body(&mut body).unwrap();
+        let header = Header::new(Segment::ID, VInt64::new(body.len() as u64)).with_size_width(8);
+        let mut expected = vec![0xAA; 4];
+        segment.write_element(&header, &mut expected).unwrap();
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_validate_webm() {
+        let atom = |codec: u64| ChapterAtom {
+            chapter_uid: ChapterUid(1),
+            chap_process: vec![ChapProcess {
+                chap_process_codec_id: ChapProcessCodecId(codec),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut segment = Segment {
+            chapters: Some(Chapters {
+                edition_entry: vec![EditionEntry {
+                    chapter_atom: vec![atom(0)],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        segment.validate_webm().unwrap();
+
+        // a DVD ChapProcess nested in a sub-chapter
+        let chapters = segment.chapters.as_mut().unwrap();
+        chapters.edition_entry[0].chapter_atom[0]
+            .chapter_atom
+            .push(atom(1));
+        assert!(chapters.edition_entry[0].chapter_atom[0].chapter_atom[0].chap_process[0].is_dvd());
+        assert!(matches!(
+            segment.validate_webm(),
+            Err(crate::Error::InvalidWebm { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_all() {
+        use crate::element::Element;
+        use crate::io::blocking_impl::WriteTo;
+        use crate::supplement::Void;
+
+        let seeks: Vec<_> = [(Info::ID, 100u64), (Tracks::ID, 200), (Cues::ID, 300)]
+            .into_iter()
+            .map(|(id, position)| Seek {
+                crc32: None,
+                void: None,
+                unknown: vec![],
+                size_widths: Default::default(),
+                seek_id: SeekId(Bytes::copy_from_slice(
+                    &id.as_encoded().unwrap().to_be_bytes()[4..],
+                )),
+                seek_position: SeekPosition(position),
+            })
+            .collect();
+        let mut buf = vec![];
+        for seek in &seeks {
+            seek.write_to(&mut buf).unwrap();
+        }
+        assert_eq!(Seek::decode_all(&mut &buf[..]).unwrap(), seeks);
+        assert!(Seek::decode_all(&mut &[][..]).unwrap().is_empty());
+
+        Void { size: 2 }.write_to(&mut buf).unwrap();
+        assert!(matches!(
+            Seek::decode_all(&mut &buf[..]),
+            Err(crate::Error::UnexpectedElement { expected, found })
+                if expected == Seek::ID && found == Void::ID
+        ));
+    }
+
+    #[test]
+    fn test_segment_builder() {
+        use crate::io::blocking_impl::{ReadFrom, WriteTo};
+
+        let mut builder = SegmentBuilder::new(Info {
+            title: Some(Title("built".to_string())),
+            ..Default::default()
+        });
+        builder
+            .add_track(TrackEntry {
+                track_number: TrackNumber(1),
+                track_uid: TrackUid(1),
+                track_type: TrackType(2),
+                codec_id: CodecId("A_OPUS".to_string()),
+                audio: Some(Audio::default()),
+                ..Default::default()
+            })
+            .add_cluster(Cluster {
+                timestamp: Timestamp(0),
+                blocks: vec![SimpleBlock(Bytes::from_static(&[0x81, 0x00, 0x00, 0x80, 1])).into()],
+                ..Default::default()
+            })
+            .add_tag(Tag::default());
+        let segment = builder.build().unwrap();
+        assert_eq!(segment.tags.len(), 1);
+
+        let mut buf = vec![];
+        segment.write_to(&mut buf).unwrap();
+        assert_eq!(Segment::read_from(&mut &buf[..]).unwrap(), segment);
+
+        // a block of a track that was never added
+        builder.add_cluster(Cluster {
+            blocks: vec![SimpleBlock(Bytes::from_static(&[0x82, 0x00, 0x00, 0x80, 1])).into()],
+            ..Default::default()
+        });
+        assert!(matches!(
+            builder.build(),
+            Err(crate::Error::UnknownTrackReference(2))
+        ));
+    }
+
+    #[test]
+    fn test_validate_linking() {
+        let uuid = Bytes::from_static(&[0x42; 16]);
+        let mut info = Info {
+            segment_uuid: Some(SegmentUuid(uuid.clone())),
+            prev_uuid: Some(PrevUuid(Bytes::from_static(&[0x01; 16]))),
+            next_uuid: Some(NextUuid(Bytes::from_static(&[0x02; 16]))),
+            ..Default::default()
+        };
+        info.validate_linking().unwrap();
+
+        let is_linking =
+            |r: crate::Result<()>| matches!(r, Err(crate::Error::InvalidLinking { .. }));
+
+        let mut prev = info.clone();
+        prev.prev_uuid = Some(PrevUuid(uuid.clone()));
+        assert!(is_linking(prev.validate_linking()));
+
+        let mut next = info.clone();
+        next.next_uuid = Some(NextUuid(uuid.clone()));
+        assert!(is_linking(next.validate_linking()));
+
+        info.chapter_translate.push(ChapterTranslate {
+            crc32: None,
+            void: None,
+            unknown: vec![],
+            size_widths: Default::default(),
+            chapter_translate_id: ChapterTranslateId(Bytes::from_static(&[0x01])),
+            chapter_translate_codec: ChapterTranslateCodec(1),
+            chapter_translate_edition_uid: vec![],
+        });
+        assert!(is_linking(info.validate_linking()));
+        info.segment_family
+            .push(SegmentFamily(Bytes::from_static(&[0x03; 16])));
+        info.validate_linking().unwrap();
+    }
+}
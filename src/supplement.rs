@@ -1,7 +1,11 @@
+use std::collections::{BTreeMap, VecDeque};
 use std::ops::Deref;
 
-use crate::base::VInt64;
+use crate::base::{Header, VInt64};
 use crate::element::Element;
+use crate::frame::ClusterBlock;
+use crate::leaf::SimpleBlock;
+use crate::master::BlockGroup;
 
 use crate::*;
 
@@ -11,7 +15,8 @@ use crate::*;
 /// Every Master element contains an optional Void element at the end of its body, which is used for padding.
 /// This library automatically aggregates multiple Void elements into one at the end.
 /// * When reading, all Void elements at the same level will be counted as one, sizes are accumulated.
-/// * When writing, only one Void element will be written at the end, with size equal to the sum of all Void elements at the same level.
+/// * When writing, only one Void element will be written at the end, with size equal to the sum of all Void elements at the same level,
+///   unless the layout of the children was recorded in [`SizeWidths`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Void {
     /// Size of the void element in bytes.
@@ -30,6 +35,218 @@ impl Element for Void {
     }
 }
 
+/// An element unknown to this library, kept as is to be written back unchanged.
+///
+/// Master elements only keep unknown children when decoding with `preserve_unknown` set in
+/// [`DecodeOptions`](crate::element::DecodeOptions), e.g. to preserve vendor extensions when remuxing.
+/// They are written after the known children, before the Void element, unless the layout of the
+/// children was recorded in [`SizeWidths`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawElement {
+    /// Header of the element, the size width is kept for a byte-exact round-trip.
+    pub header: Header,
+    /// Body of the element.
+    pub body: Bytes,
+}
+impl Encode for RawElement {
+    fn encode<B: BufMut>(&self, buf: &mut B) -> crate::Result<()> {
+        self.header.encode(buf)?;
+        buf.put_slice(&self.body);
+        Ok(())
+    }
+}
+
+/// Layout of the children of a master element, i.e. the widths of their encoded sizes and their
+/// order, for a byte-exact round-trip of the layout.
+///
+/// Master elements only record it when decoding with `preserve_size_width` set in
+/// [`DecodeOptions`](crate::element::DecodeOptions), as some muxers encode sizes on more bytes
+/// than needed. Children are then written back in the recorded order, each with the width recorded
+/// for the child with the same ID at the same index, unless its size no longer fits in it.
+/// Void elements are written back one by one, as long as their total size is unchanged.
+/// Children added since decoding are written after the recorded ones, in the default order.
+///
+/// Leaf bodies are always re-encoded, so a value encoded on more bytes than needed is not preserved.
+/// The layout is not part of the value: it is ignored by `PartialEq` and `Hash`.
+#[derive(Debug, Clone, Default)]
+pub struct SizeWidths {
+    /// Size widths of the children, by ID then index.
+    widths: BTreeMap<VInt64, Vec<usize>>,
+    /// IDs of the children, in the order they were decoded.
+    order: Vec<VInt64>,
+    /// Body sizes of the Void children, which are merged into one when decoding.
+    voids: Vec<u64>,
+}
+
+impl PartialEq for SizeWidths {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+impl Eq for SizeWidths {}
+impl std::hash::Hash for SizeWidths {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
+impl SizeWidths {
+    /// Record the size width and position of the next child, given its header.
+    pub(crate) fn record(&mut self, header: &Header) {
+        if let Some(width) = header.size_width() {
+            self.widths.entry(header.id).or_default().push(width);
+        }
+        self.order.push(header.id);
+        if header.id == Void::ID {
+            self.voids.push(*header.size);
+        }
+    }
+
+    /// The size width recorded for the `index`-th child with the given ID.
+    pub fn get(&self, id: VInt64, index: usize) -> Option<usize> {
+        self.widths.get(&id)?.get(index).copied()
+    }
+
+    /// Whether nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.widths.is_empty() && self.order.is_empty()
+    }
+
+    /// Encode `element`, the `index`-th child with its ID, with the size width recorded for it.
+    pub(crate) fn encode<T: Element, B: BufMut>(
+        &self,
+        element: &T,
+        index: usize,
+        buf: &mut B,
+    ) -> crate::Result<()> {
+        let Some(width) = self.get(T::ID, index) else {
+            return element.encode(buf);
+        };
+        let mut body = vec![];
+        element.encode_body(&mut body)?;
+        let mut header = Header::new(T::ID, VInt64::new(body.len() as u64));
+        // a body that grew since decoding falls back to the minimal width
+        if VInt64::encode_size(body.len() as u64) <= width {
+            header = header.with_size_width(width);
+        }
+        header.encode(buf)?;
+        buf.put_slice(&body);
+        Ok(())
+    }
+
+    /// Write `children`, given in the default order, and `void` in the recorded layout,
+    /// passing each encoded child to `write`.
+    ///
+    /// The CRC-32 is always written first, as required by EBML.
+    pub(crate) fn write_children(
+        &self,
+        children: &[&dyn Child],
+        void: Option<&Void>,
+        mut write: impl FnMut(&[u8]) -> crate::Result<()>,
+    ) -> crate::Result<()> {
+        // the Voids merged when decoding are split back, unless their total size changed
+        let voids: Vec<Void> = match void {
+            Some(void) if !self.voids.is_empty() && self.voids.iter().sum::<u64>() == void.size => {
+                self.voids.iter().map(|&size| Void { size }).collect()
+            }
+            Some(void) => vec![*void],
+            None => vec![],
+        };
+        let slots: Vec<&dyn Child> = children
+            .iter()
+            .copied()
+            .chain(voids.iter().map(|v| v as &dyn Child))
+            .collect();
+
+        // index of every slot among the children with the same ID, and the slots left by ID
+        let mut indices = Vec::with_capacity(slots.len());
+        let mut left: BTreeMap<VInt64, VecDeque<usize>> = BTreeMap::new();
+        for (slot, child) in slots.iter().enumerate() {
+            let queue = left.entry(child.id()).or_default();
+            indices.push(queue.len());
+            queue.push_back(slot);
+        }
+
+        let mut sequence = Vec::with_capacity(slots.len());
+        for id in std::iter::once(&Crc32::ID).chain(&self.order) {
+            if let Some(slot) = left.get_mut(id).and_then(|queue| queue.pop_front()) {
+                sequence.push(slot);
+            }
+        }
+        let mut rest: Vec<usize> = left.into_values().flatten().collect();
+        rest.sort_unstable();
+        sequence.extend(rest);
+
+        let mut scratch = vec![];
+        for slot in sequence {
+            scratch.clear();
+            slots[slot].encode_child(self, indices[slot], &mut scratch)?;
+            write(&scratch)?;
+        }
+        Ok(())
+    }
+}
+
+/// A child of a master element, written in the layout recorded in [`SizeWidths`].
+pub(crate) trait Child {
+    /// ID of the child.
+    fn id(&self) -> VInt64;
+    /// Encode the child, the `index`-th with its ID, with the size width recorded for it.
+    fn encode_child(
+        &self,
+        widths: &SizeWidths,
+        index: usize,
+        buf: &mut Vec<u8>,
+    ) -> crate::Result<()>;
+}
+
+impl<T: Element> Child for T {
+    fn id(&self) -> VInt64 {
+        T::ID
+    }
+    fn encode_child(
+        &self,
+        widths: &SizeWidths,
+        index: usize,
+        buf: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        widths.encode(self, index, buf)
+    }
+}
+
+impl Child for RawElement {
+    fn id(&self) -> VInt64 {
+        self.header.id
+    }
+    fn encode_child(
+        &self,
+        _widths: &SizeWidths,
+        _index: usize,
+        buf: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        // the header of an unknown element keeps its own size width
+        self.encode(buf)
+    }
+}
+
+impl Child for ClusterBlock {
+    fn id(&self) -> VInt64 {
+        match self {
+            ClusterBlock::Simple(_) => SimpleBlock::ID,
+            ClusterBlock::Group(_) => BlockGroup::ID,
+        }
+    }
+    fn encode_child(
+        &self,
+        widths: &SizeWidths,
+        index: usize,
+        buf: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        match self {
+            ClusterBlock::Simple(b) => widths.encode(b, index, buf),
+            ClusterBlock::Group(g) => widths.encode(g, index, buf),
+        }
+    }
+}
+
 /// CRC-32 element, used for integrity checking. The CRC-32 is stored as a little-endian u32.
 ///
 /// ### Note:
@@ -43,6 +260,42 @@ impl Deref for Crc32 {
         &self.0
     }
 }
+/// Lookup table for the CRC-32 (IEEE 802.3) polynomial, in reflected form.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+impl Crc32 {
+    /// Compute the CRC-32 of the given data, as used by EBML (IEEE 802.3 polynomial).
+    pub fn compute(data: &[u8]) -> Self {
+        let crc = data.iter().fold(!0u32, |crc, &b| {
+            CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+        });
+        Self(!crc)
+    }
+
+    /// Whether the given data matches this CRC-32.
+    pub fn verify(&self, data: &[u8]) -> bool {
+        Self::compute(data) == *self
+    }
+}
+
 impl Element for Crc32 {
     const ID: VInt64 = VInt64::from_encoded(0xBF);
     fn decode_body(buf: &mut dyn Buf) -> crate::Result<Self> {
@@ -54,3 +307,16 @@ impl Element for Crc32 {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_compute() {
+        assert_eq!(*Crc32::compute(b"123456789"), 0xCBF4_3926);
+        assert_eq!(*Crc32::compute(b""), 0);
+        assert!(Crc32(0xCBF4_3926).verify(b"123456789"));
+        assert!(!Crc32(0xCBF4_3926).verify(b"123456780"));
+    }
+}
@@ -0,0 +1,172 @@
+//! Helpers for working with tags.
+
+use crate::Error;
+use crate::leaf::{TagName, TagString, TargetTypeValue};
+use crate::master::{SimpleTag, Tag, Tags, Targets};
+
+impl Tags {
+    /// Tags describing a whole movie (`TargetTypeValue` 50), with a `TITLE` and optionally
+    /// `DATE_RELEASED` and `COMMENT`, laid out as in the Matroska test files.
+    pub fn movie_metadata(title: &str, date: Option<&str>, comment: Option<&str>) -> Tags {
+        let simple_tag = |name: &str, value: &str| SimpleTag {
+            tag_name: TagName(name.to_string()),
+            tag_string: Some(TagString(value.to_string())),
+            ..Default::default()
+        };
+        let mut simple_tags = vec![simple_tag("TITLE", title)];
+        simple_tags.extend(date.map(|date| simple_tag("DATE_RELEASED", date)));
+        simple_tags.extend(comment.map(|comment| simple_tag("COMMENT", comment)));
+        Tags {
+            tag: vec![Tag {
+                targets: Targets {
+                    target_type_value: TargetTypeValue(50),
+                    ..Default::default()
+                },
+                simple_tag: simple_tags,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// The string value of the first `SimpleTag` named `name`, e.g. "TITLE", in any `Tag`.
+    ///
+    /// Nested `SimpleTag`s are not searched, as they qualify their parent.
+    pub fn find(&self, name: &str) -> Option<&str> {
+        self.tag
+            .iter()
+            .flat_map(|tag| &tag.simple_tag)
+            .filter(|simple| &*simple.tag_name == name)
+            .find_map(|simple| simple.tag_string.as_deref())
+    }
+}
+
+impl SimpleTag {
+    /// Check that the tag has at most one value: `TagString` and `TagBinary` cannot be used together.
+    ///
+    /// Both being set gives [`Error::ConflictingTagValue`]. Decoding in strict mode runs this check.
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.tag_string.is_some() && self.tag_binary.is_some() {
+            return Err(Error::ConflictingTagValue(self.tag_name.to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl Targets {
+    /// Whether no UID is given, so the tag describes the whole Segment.
+    fn is_global(&self) -> bool {
+        self.tag_track_uid.is_empty()
+            && self.tag_edition_uid.is_empty()
+            && self.tag_chapter_uid.is_empty()
+            && self.tag_attachment_uid.is_empty()
+    }
+
+    /// Whether the tag applies to the UID among `uids`, where a UID of 0 stands for all of them.
+    fn applies_to(&self, mut uids: impl Iterator<Item = u64>, uid: u64) -> bool {
+        if self.is_global() {
+            return true;
+        }
+        uids.any(|target| target == 0 || target == uid)
+    }
+
+    /// Whether the tag applies to the track with the given `TrackUID`.
+    ///
+    /// Tags without any target UID apply to everything in the Segment. Otherwise the track must
+    /// be listed in `TagTrackUID`, where a value of 0 means all tracks.
+    pub fn applies_to_track(&self, uid: u64) -> bool {
+        self.applies_to(self.tag_track_uid.iter().map(|u| **u), uid)
+    }
+
+    /// Whether the tag applies to the edition with the given `EditionUID`,
+    /// see [`Targets::applies_to_track`].
+    pub fn applies_to_edition(&self, uid: u64) -> bool {
+        self.applies_to(self.tag_edition_uid.iter().map(|u| **u), uid)
+    }
+
+    /// Whether the tag applies to the chapter with the given `ChapterUID`,
+    /// see [`Targets::applies_to_track`].
+    pub fn applies_to_chapter(&self, uid: u64) -> bool {
+        self.applies_to(self.tag_chapter_uid.iter().map(|u| **u), uid)
+    }
+
+    /// Whether the tag applies to the attachment with the given `FileUID`,
+    /// see [`Targets::applies_to_track`].
+    pub fn applies_to_attachment(&self, uid: u64) -> bool {
+        self.applies_to(self.tag_attachment_uid.iter().map(|u| **u), uid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use crate::leaf::*;
+    use crate::master::{Tags, Targets};
+
+    #[test]
+    fn test_applies_to() {
+        let global = Targets::default();
+        assert!(global.applies_to_track(12));
+        assert!(global.applies_to_attachment(3));
+
+        let track_scoped = Targets {
+            tag_track_uid: vec![TagTrackUid(12)],
+            ..Default::default()
+        };
+        assert!(track_scoped.applies_to_track(12));
+        assert!(!track_scoped.applies_to_track(13));
+        assert!(!track_scoped.applies_to_chapter(12));
+
+        let all_tracks = Targets {
+            tag_track_uid: vec![TagTrackUid(0)],
+            ..Default::default()
+        };
+        assert!(all_tracks.applies_to_track(13));
+        assert!(!all_tracks.applies_to_edition(1));
+    }
+
+    #[test]
+    fn test_simple_tag_validate() {
+        use crate::ErrorCode;
+        use crate::element::{DecodeOptions, Strictness};
+        use crate::io::blocking_impl::{ReadFrom, ReadFromWith, WriteTo};
+        use crate::master::SimpleTag;
+
+        let mut tag = SimpleTag {
+            tag_name: TagName("COVER".to_string()),
+            tag_string: Some(TagString("cover.jpg".to_string())),
+            ..Default::default()
+        };
+        tag.validate().unwrap();
+        tag.tag_binary = Some(TagBinary(vec![0xFF, 0xD8].into()));
+        assert_eq!(
+            tag.validate().unwrap_err().code(),
+            ErrorCode::ConflictingTagValue
+        );
+
+        let mut buf = vec![];
+        tag.write_to(&mut buf).unwrap();
+        SimpleTag::read_from(&mut &buf[..]).unwrap();
+        let strict = DecodeOptions::with_strictness(Strictness::Strict);
+        let err = SimpleTag::read_from_with(&mut &buf[..], &strict).unwrap_err();
+        assert!(matches!(err, Error::ConflictingTagValue(name) if name == "COVER"));
+    }
+
+    #[test]
+    fn test_movie_metadata() {
+        use crate::io::blocking_impl::{ReadFrom, WriteTo};
+
+        let tags = Tags::movie_metadata("Big Buck Bunny", Some("2008"), Some("Open movie"));
+        let mut buf = vec![];
+        tags.write_to(&mut buf).unwrap();
+        let tags = Tags::read_from(&mut &buf[..]).unwrap();
+        assert_eq!(*tags.tag[0].targets.target_type_value, 50);
+        assert_eq!(tags.find("TITLE"), Some("Big Buck Bunny"));
+        assert_eq!(tags.find("DATE_RELEASED"), Some("2008"));
+        assert_eq!(tags.find("COMMENT"), Some("Open movie"));
+
+        let tags = Tags::movie_metadata("Untitled", None, None);
+        assert_eq!(tags.tag[0].simple_tag.len(), 1);
+        assert_eq!(tags.find("COMMENT"), None);
+    }
+}
@@ -0,0 +1,710 @@
+//! Helpers for working with tracks.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::leaf::{BlockAddIdType, TrackPlaneType};
+use crate::master::{ContentEncoding, ContentEncodings, TrackEntry, Tracks};
+use crate::*;
+
+/// A `TrackOperation` with its UID references resolved to the `TrackEntry`s they point to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedOperation<'a> {
+    /// Tracks combined into the virtual track, with the plane each one contributes.
+    pub combine_planes: Vec<(TrackPlaneType, &'a TrackEntry)>,
+    /// Tracks whose blocks are joined into the virtual track, in order.
+    pub join_blocks: Vec<&'a TrackEntry>,
+}
+
+/// Tracks indexed by track number, built once for repeated lookups on hot paths.
+#[derive(Debug, Clone, Default)]
+pub struct TrackIndex<'a> {
+    by_number: HashMap<u64, &'a TrackEntry>,
+}
+
+impl<'a> TrackIndex<'a> {
+    /// Get the track with the given track number.
+    pub fn get(&self, track_number: u64) -> Option<&'a TrackEntry> {
+        self.by_number.get(&track_number).copied()
+    }
+}
+
+impl TrackEntry {
+    /// Undo the compressions applied to the frames of the track, in decoding order.
+    ///
+    /// Same as [`TrackEntry::decode_frame_with`] with `decompress` set and no decryption key.
+    pub fn decode_frame<'a>(&self, data: &'a [u8]) -> crate::Result<Cow<'a, [u8]>> {
+        let options = FrameOptions {
+            decompress: true,
+            ..Default::default()
+        };
+        self.decode_frame_with(data, &options)
+    }
+
+    /// Undo the `ContentEncoding`s applied to the frames of the track, in decoding order.
+    ///
+    /// With `decompress`, header stripping is undone by prepending the stripped bytes, and zlib
+    /// is inflated with the `zlib` feature. With a `decrypt_key`, AES-CTR encryption as used by
+    /// WebM is undone with the `encryption` feature. An encrypted track without a key gives
+    /// [`Error::MissingDecryptionKey`], and other algorithms give
+    /// [`Error::UnsupportedContentEncoding`]. Decoding stops at the first compression when
+    /// `decompress` is unset, as the following encodings apply to the compressed data. The data is
+    /// borrowed as is when nothing had to be undone.
+    pub fn decode_frame_with<'a>(
+        &self,
+        data: &'a [u8],
+        options: &FrameOptions,
+    ) -> crate::Result<Cow<'a, [u8]>> {
+        let Some(encodings) = &self.content_encodings else {
+            return Ok(Cow::Borrowed(data));
+        };
+        let track = *self.track_number;
+        let mut data = Cow::Borrowed(data);
+        // bit 0x1 of the scope: the encoding applies to the frames
+        for encoding in encodings
+            .in_decode_order()?
+            .into_iter()
+            .filter(|e| *e.content_encoding_scope & 1 != 0)
+        {
+            match *encoding.content_encoding_type {
+                0 if !options.decompress => break,
+                0 => {
+                    let compression = encoding
+                        .content_compression
+                        .as_ref()
+                        .ok_or(Error::UnsupportedContentEncoding(track))?;
+                    match *compression.content_comp_algo {
+                        3 => {
+                            let header =
+                                compression.content_comp_settings.as_deref().unwrap_or(&[]);
+                            data = Cow::Owned([header, &data].concat());
+                        }
+                        #[cfg(feature = "zlib")]
+                        0 => {
+                            use std::io::Read;
+                            let mut inflated = vec![];
+                            flate2::read::ZlibDecoder::new(&data[..]).read_to_end(&mut inflated)?;
+                            data = Cow::Owned(inflated);
+                        }
+                        _ => return Err(Error::UnsupportedContentEncoding(track)),
+                    }
+                }
+                1 => {
+                    let key = options
+                        .decrypt_key
+                        .as_ref()
+                        .ok_or(Error::MissingDecryptionKey(track))?;
+                    let encryption = encoding
+                        .content_encryption
+                        .as_ref()
+                        .ok_or(Error::UnsupportedContentEncoding(track))?;
+                    let ctr = encryption
+                        .content_enc_aes_settings
+                        .as_ref()
+                        .is_some_and(|aes| *aes.aes_settings_cipher_mode == 1);
+                    match *encryption.content_enc_algo {
+                        #[cfg(feature = "encryption")]
+                        5 if ctr => data = Cow::Owned(decrypt_webm(track, &data, key)?),
+                        _ => {
+                            // only used with the `encryption` feature
+                            let _ = (key, ctr);
+                            return Err(Error::UnsupportedContentEncoding(track));
+                        }
+                    }
+                }
+                _ => return Err(Error::UnsupportedContentEncoding(track)),
+            }
+        }
+        Ok(data)
+    }
+
+    /// The timestamp at which a frame is played, from its timestamp in nanoseconds,
+    /// with the `CodecDelay` subtracted as the specification requires.
+    pub fn adjust_timestamp(&self, ts_ns: i64) -> i64 {
+        ts_ns.saturating_sub_unsigned(*self.codec_delay)
+    }
+
+    /// How much data the decoder must decode after a seek before its output is valid,
+    /// from the `SeekPreRoll`, e.g. 80ms for Opus.
+    pub fn seek_pre_roll(&self) -> std::time::Duration {
+        std::time::Duration::from_nanos(*self.seek_pre_roll)
+    }
+
+    /// The language of the track, taking `LanguageBCP47` over the legacy `Language` when set,
+    /// as the specification requires.
+    pub fn effective_language(&self) -> &str {
+        match &self.language_bcp47 {
+            Some(bcp47) => &bcp47.0,
+            None => &self.language.0,
+        }
+    }
+
+    /// Check the track for authoring mistakes the element structure alone does not catch.
+    ///
+    /// * a `V_UNCOMPRESSED` track must have a 4-byte `UncompressedFourCC`, otherwise
+    ///   [`Error::MissingFourcc`] is returned.
+    /// * the video crops must leave part of the picture, see [`Video::validate_crop`](crate::prelude::Video::validate_crop).
+    pub fn validate(&self) -> crate::Result<()> {
+        if &*self.codec_id == "V_UNCOMPRESSED"
+            && self.video.as_ref().and_then(|v| v.fourcc()).is_none()
+        {
+            return Err(Error::MissingFourcc(*self.track_number));
+        }
+        if let Some(video) = &self.video {
+            video.validate_crop()?;
+        }
+        Ok(())
+    }
+
+    /// Check that the `Video` and `Audio` settings match the `TrackType`.
+    ///
+    /// A video track must have `Video` settings and no `Audio` ones, an audio track the
+    /// opposite. Complex tracks may have both, and the other types, such as subtitles, neither.
+    /// A mismatch gives [`Error::TrackTypeMismatch`].
+    pub fn validate_type_consistency(&self) -> crate::Result<()> {
+        let (video, audio) = (self.video.is_some(), self.audio.is_some());
+        let consistent = match *self.track_type {
+            1 => video && !audio,
+            2 => audio && !video,
+            3 => true,
+            _ => !video && !audio,
+        };
+        if consistent {
+            Ok(())
+        } else {
+            Err(Error::TrackTypeMismatch {
+                track: *self.track_number,
+                track_type: *self.track_type,
+            })
+        }
+    }
+}
+
+impl Tracks {
+    /// Iterate over the tracks, in the order they are described.
+    ///
+    /// `&Tracks` is also iterable, so the tracks can be looped over directly:
+    /// ```
+    /// use mkv_element::prelude::*;
+    ///
+    /// let tracks = Tracks {
+    ///     track_entry: vec![
+    ///         TrackEntry { track_number: TrackNumber(1), ..Default::default() },
+    ///         TrackEntry { track_number: TrackNumber(2), ..Default::default() },
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    /// let mut numbers = vec![];
+    /// for track in &tracks {
+    ///     numbers.push(*track.track_number);
+    /// }
+    /// assert_eq!(numbers, vec![1, 2]);
+    /// assert_eq!(tracks.iter().count(), 2);
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, TrackEntry> {
+        self.track_entry.iter()
+    }
+
+    /// Get the track with the given track number.
+    pub fn by_number(&self, track_number: u64) -> Option<&TrackEntry> {
+        self.track_entry
+            .iter()
+            .find(|t| *t.track_number == track_number)
+    }
+
+    /// Get the track with the given track UID.
+    pub fn by_uid(&self, track_uid: u64) -> Option<&TrackEntry> {
+        self.iter().find(|t| *t.track_uid == track_uid)
+    }
+
+    /// Build an index of the tracks by track number.
+    ///
+    /// If several tracks share a track number, the first one wins, matching [`Tracks::by_number`].
+    pub fn index(&self) -> TrackIndex<'_> {
+        let mut by_number = HashMap::with_capacity(self.track_entry.len());
+        for track in &self.track_entry {
+            by_number.entry(*track.track_number).or_insert(track);
+        }
+        TrackIndex { by_number }
+    }
+
+    /// Resolve the `TrackOperation` of a track against the tracks in this element.
+    ///
+    /// Returns `Ok(None)` if the track has no `TrackOperation`,
+    /// and [`Error::TrackNotFound`] if it references a UID that is not present.
+    pub fn resolve_operation(
+        &self,
+        entry: &TrackEntry,
+    ) -> crate::Result<Option<ResolvedOperation<'_>>> {
+        let Some(operation) = &entry.track_operation else {
+            return Ok(None);
+        };
+        let find = |uid: u64| self.by_uid(uid).ok_or(Error::TrackNotFound(uid));
+
+        let combine_planes = operation
+            .track_combine_planes
+            .iter()
+            .flat_map(|c| c.track_plane.iter())
+            .map(|p| Ok((p.track_plane_type, find(*p.track_plane_uid)?)))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let join_blocks = operation
+            .track_join_blocks
+            .iter()
+            .flat_map(|j| j.track_join_uid.iter())
+            .map(|uid| find(**uid))
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        Ok(Some(ResolvedOperation {
+            combine_planes,
+            join_blocks,
+        }))
+    }
+}
+
+impl<'a> IntoIterator for &'a Tracks {
+    type Item = &'a TrackEntry;
+    type IntoIter = std::slice::Iter<'a, TrackEntry>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.track_entry.iter()
+    }
+}
+
+/// Registered kinds of `BlockAddIDType`, from the Matroska Block Additional Mapping registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BlockAddIdKind {
+    /// Codec-specific data, identified by `BlockAddIDValue` (e.g. the WebM alpha channel).
+    CodecSpecific,
+    /// Opaque data that is not interpreted by the player.
+    Opaque,
+    /// ITU-T T.35 metadata, such as HDR10+.
+    ItuT35,
+    /// SMPTE ST 12-1 timecode.
+    SmpteTimecode,
+    /// Dolby Vision configuration, `dvcC`.
+    DolbyVisionConfig,
+    /// Dolby Vision configuration for profiles above 7, `dvvC`.
+    DolbyVisionConfigV2,
+    /// Dolby Vision configuration for profiles 20 and above, `dvwC`.
+    DolbyVisionConfigV3,
+    /// Dolby Vision enhancement layer HEVC configuration, `hvcE`.
+    HevcEnhancementLayer,
+    /// MVC configuration, `mvcC`.
+    MvcConfig,
+    /// A value not in the registry.
+    Unknown(u64),
+}
+
+impl ContentEncodings {
+    /// The encodings in the order to undo them when reading, highest `ContentEncodingOrder` first.
+    ///
+    /// Returns [`Error::DuplicateEncodingOrder`] if two encodings share an order, which the
+    /// specification forbids as the order would then be ambiguous.
+    pub fn in_decode_order(&self) -> crate::Result<Vec<&ContentEncoding>> {
+        let mut encodings: Vec<_> = self.content_encoding.iter().collect();
+        encodings.sort_by_key(|e| std::cmp::Reverse(*e.content_encoding_order));
+        if let Some(w) = encodings
+            .windows(2)
+            .find(|w| w[0].content_encoding_order == w[1].content_encoding_order)
+        {
+            return Err(Error::DuplicateEncodingOrder(*w[0].content_encoding_order));
+        }
+        Ok(encodings)
+    }
+}
+
+impl BlockAddIdType {
+    /// Create a `BlockAddIDType` from a registered 4CC, e.g. `b"dvcC"`.
+    pub fn from_fourcc(fourcc: &[u8; 4]) -> Self {
+        Self(u32::from_be_bytes(*fourcc) as u64)
+    }
+
+    /// The value as a 4CC, e.g. `*b"dvcC"`. Only the lower 32 bits are used.
+    pub fn as_fourcc(&self) -> [u8; 4] {
+        (self.0 as u32).to_be_bytes()
+    }
+
+    /// The registered kind of this `BlockAddIDType`.
+    pub fn kind(&self) -> BlockAddIdKind {
+        match self.0 {
+            0 => BlockAddIdKind::CodecSpecific,
+            1 => BlockAddIdKind::Opaque,
+            4 => BlockAddIdKind::ItuT35,
+            121 => BlockAddIdKind::SmpteTimecode,
+            v if v > u32::MAX as u64 => BlockAddIdKind::Unknown(v),
+            _ => match &self.as_fourcc() {
+                b"dvcC" => BlockAddIdKind::DolbyVisionConfig,
+                b"dvvC" => BlockAddIdKind::DolbyVisionConfigV2,
+                b"dvwC" => BlockAddIdKind::DolbyVisionConfigV3,
+                b"hvcE" => BlockAddIdKind::HevcEnhancementLayer,
+                b"mvcC" => BlockAddIdKind::MvcConfig,
+                _ => BlockAddIdKind::Unknown(self.0),
+            },
+        }
+    }
+}
+
+/// Decrypt a frame encrypted with AES-CTR as specified by WebM.
+///
+/// The frame starts with a signal byte: when its encrypted bit is set, an 8-byte IV follows,
+/// then, when its partitioned bit is set, the offsets of the partitions, alternately clear and
+/// encrypted starting with a clear one. The encrypted partitions share one keystream, with the
+/// IV as the high half of the counter block.
+#[cfg(feature = "encryption")]
+fn decrypt_webm(track: u64, data: &[u8], key: &[u8; 16]) -> crate::Result<Vec<u8>> {
+    use aes::cipher::{KeyIvInit, StreamCipher};
+
+    let invalid = || Error::InvalidEncryptedFrame(track);
+    let (&signal, rest) = data.split_first().ok_or_else(invalid)?;
+    if signal & 0x01 == 0 {
+        return Ok(rest.to_vec());
+    }
+    let (iv, mut rest) = rest.split_at_checked(8).ok_or_else(invalid)?;
+    // an unpartitioned frame is a single encrypted partition
+    let mut offsets = vec![0];
+    if signal & 0x02 != 0 {
+        let (&count, tail) = rest.split_first().ok_or_else(invalid)?;
+        let (table, payload) = tail
+            .split_at_checked(4 * count as usize)
+            .ok_or_else(invalid)?;
+        offsets = table
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]) as usize)
+            .collect();
+        rest = payload;
+    }
+    let mut counter = [0u8; 16];
+    counter[..8].copy_from_slice(iv);
+    let mut cipher = ctr::Ctr64BE::<aes::Aes128>::new(key.into(), &counter.into());
+    let mut out = rest.to_vec();
+    let bounds: Vec<_> = std::iter::once(0)
+        .chain(offsets)
+        .chain(std::iter::once(out.len()))
+        .collect();
+    for (i, w) in bounds.windows(2).enumerate() {
+        if w[0] > w[1] || w[1] > out.len() {
+            return Err(invalid());
+        }
+        if i % 2 == 1 {
+            cipher.apply_keystream(&mut out[w[0]..w[1]]);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn track(number: u64, uid: u64) -> TrackEntry {
+        TrackEntry {
+            track_number: TrackNumber(number),
+            track_uid: TrackUid(uid),
+            track_type: TrackType(1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_block_add_id_type_fourcc() {
+        let dvcc = BlockAddIdType::from_fourcc(b"dvcC");
+        assert_eq!(*dvcc, 0x6476_6343);
+        assert_eq!(&dvcc.as_fourcc(), b"dvcC");
+        assert_eq!(dvcc.kind(), BlockAddIdKind::DolbyVisionConfig);
+        assert_eq!(BlockAddIdType(0).kind(), BlockAddIdKind::CodecSpecific);
+        assert_eq!(BlockAddIdType(4).kind(), BlockAddIdKind::ItuT35);
+        assert_eq!(
+            BlockAddIdType(0x1_6476_6343).kind(),
+            BlockAddIdKind::Unknown(0x1_6476_6343)
+        );
+    }
+
+    #[test]
+    fn test_codec_delay() {
+        let mut opus = track(1, 100);
+        opus.codec_id = CodecId("A_OPUS".to_string());
+        opus.codec_delay = CodecDelay(6_500_000);
+        opus.seek_pre_roll = SeekPreRoll(80_000_000);
+
+        assert_eq!(opus.adjust_timestamp(0), -6_500_000);
+        assert_eq!(opus.adjust_timestamp(20_000_000), 13_500_000);
+        assert_eq!(opus.seek_pre_roll(), std::time::Duration::from_millis(80));
+        assert_eq!(track(2, 200).adjust_timestamp(1000), 1000);
+    }
+
+    #[test]
+    fn test_effective_language() {
+        let mut entry = track(1, 100);
+        assert_eq!(entry.effective_language(), "eng");
+
+        entry.language = Language("ger".to_string());
+        assert_eq!(entry.effective_language(), "ger");
+
+        entry.language_bcp47 = Some(LanguageBcp47("de-CH".to_string()));
+        assert_eq!(entry.effective_language(), "de-CH");
+    }
+
+    #[test]
+    fn test_validate_uncompressed_fourcc() {
+        let mut entry = track(1, 100);
+        entry.codec_id = CodecId("V_UNCOMPRESSED".to_string());
+        entry.video = Some(Video::default());
+        assert!(matches!(entry.validate(), Err(Error::MissingFourcc(1))));
+
+        entry.video.as_mut().unwrap().uncompressed_fourcc =
+            Some(UncompressedFourcc(Bytes::from_static(b"YUY")));
+        assert!(matches!(entry.validate(), Err(Error::MissingFourcc(1))));
+
+        entry.video.as_mut().unwrap().uncompressed_fourcc =
+            Some(UncompressedFourcc(Bytes::from_static(b"YUY2")));
+        assert_eq!(entry.video.as_ref().unwrap().fourcc(), Some(*b"YUY2"));
+        assert!(entry.validate().is_ok());
+
+        assert!(track(2, 200).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_type_consistency() {
+        let mut entry = track(1, 100);
+        let mismatch = |entry: &TrackEntry| {
+            matches!(
+                entry.validate_type_consistency(),
+                Err(Error::TrackTypeMismatch { track: 1, .. })
+            )
+        };
+        // a video track missing its Video element
+        assert!(mismatch(&entry));
+        entry.video = Some(Video::default());
+        assert!(entry.validate_type_consistency().is_ok());
+        entry.audio = Some(Audio::default());
+        assert!(mismatch(&entry));
+
+        // complex tracks may have both, subtitles neither
+        entry.track_type = TrackType(3);
+        assert!(entry.validate_type_consistency().is_ok());
+        entry.track_type = TrackType(17);
+        assert!(mismatch(&entry));
+        entry.video = None;
+        entry.audio = None;
+        assert!(entry.validate_type_consistency().is_ok());
+
+        entry.track_type = TrackType(2);
+        assert!(mismatch(&entry));
+        entry.audio = Some(Audio::default());
+        assert!(entry.validate_type_consistency().is_ok());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let tracks = Tracks {
+            track_entry: vec![track(1, 100), track(2, 200)],
+            ..Default::default()
+        };
+        assert_eq!(tracks.by_number(2).map(|t| *t.track_uid), Some(200));
+        assert_eq!(tracks.by_number(3), None);
+        assert_eq!(tracks.by_uid(100).map(|t| *t.track_number), Some(1));
+        assert_eq!(tracks.by_uid(1), None);
+
+        let index = tracks.index();
+        assert_eq!(index.get(1), tracks.by_number(1));
+        assert_eq!(index.get(2), tracks.by_number(2));
+        assert_eq!(index.get(3), None);
+    }
+
+    #[test]
+    fn test_resolve_combine_planes() {
+        let mut virtual_track = track(3, 300);
+        virtual_track.track_operation = Some(TrackOperation {
+            track_combine_planes: Some(TrackCombinePlanes {
+                track_plane: vec![
+                    TrackPlane {
+                        track_plane_uid: TrackPlaneUid(100),
+                        track_plane_type: TrackPlaneType(0),
+                        ..Default::default()
+                    },
+                    TrackPlane {
+                        track_plane_uid: TrackPlaneUid(200),
+                        track_plane_type: TrackPlaneType(1),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let tracks = Tracks {
+            track_entry: vec![track(1, 100), track(2, 200), virtual_track.clone()],
+            ..Default::default()
+        };
+
+        let resolved = tracks.resolve_operation(&virtual_track).unwrap().unwrap();
+        assert!(resolved.join_blocks.is_empty());
+        assert_eq!(resolved.combine_planes.len(), 2);
+        assert_eq!(resolved.combine_planes[0].0, TrackPlaneType(0));
+        assert_eq!(*resolved.combine_planes[0].1.track_number, 1);
+        assert_eq!(resolved.combine_planes[1].0, TrackPlaneType(1));
+        assert_eq!(*resolved.combine_planes[1].1.track_number, 2);
+
+        // no operation
+        assert!(tracks.resolve_operation(&track(1, 100)).unwrap().is_none());
+
+        // missing UID
+        let tracks = Tracks {
+            track_entry: vec![track(1, 100)],
+            ..Default::default()
+        };
+        assert!(matches!(
+            tracks.resolve_operation(&virtual_track),
+            Err(Error::TrackNotFound(200))
+        ));
+    }
+
+    #[cfg(feature = "zlib")]
+    #[test]
+    fn test_decode_frame_zlib() {
+        use std::io::Write;
+
+        let track = TrackEntry {
+            content_encodings: Some(ContentEncodings {
+                content_encoding: vec![ContentEncoding {
+                    content_compression: Some(ContentCompression::default()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..track(1, 1)
+        };
+        let frame = b"a frame compressed with zlib, the default ContentCompAlgo";
+        let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(frame).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(&*track.decode_frame(&compressed).unwrap(), frame);
+        // left compressed without `decompress`
+        let raw = track
+            .decode_frame_with(&compressed, &FrameOptions::default())
+            .unwrap();
+        assert!(matches!(raw, Cow::Borrowed(data) if data == compressed));
+        assert!(track.decode_frame(&[0x78]).is_err());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_decode_frame_encrypted() {
+        use aes::cipher::{KeyIvInit, StreamCipher};
+
+        let key = [0x42; 16];
+        let iv = [1, 2, 3, 4, 5, 6, 7, 8];
+        // header stripped, then encrypted as in WebM
+        let track = TrackEntry {
+            content_encodings: Some(ContentEncodings {
+                content_encoding: vec![
+                    ContentEncoding {
+                        content_compression: Some(ContentCompression {
+                            content_comp_algo: ContentCompAlgo(3),
+                            content_comp_settings: Some(ContentCompSettings(
+                                vec![0x00, 0x01].into(),
+                            )),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                    ContentEncoding {
+                        content_encoding_order: ContentEncodingOrder(1),
+                        content_encoding_type: ContentEncodingType(1),
+                        content_encryption: Some(ContentEncryption {
+                            content_enc_algo: ContentEncAlgo(5),
+                            content_enc_aes_settings: Some(ContentEncAesSettings {
+                                aes_settings_cipher_mode: AesSettingsCipherMode(1),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }),
+            ..track(1, 1)
+        };
+        let encrypt = |data: &mut [u8]| {
+            let mut counter = [0u8; 16];
+            counter[..8].copy_from_slice(&iv);
+            ctr::Ctr64BE::<aes::Aes128>::new(&key.into(), &counter.into()).apply_keystream(data);
+        };
+
+        let mut payload = b"secret".to_vec();
+        encrypt(&mut payload);
+        let frame = [&[0x01][..], &iv, &payload].concat();
+        let options = FrameOptions {
+            decompress: true,
+            decrypt_key: Some(key),
+        };
+        let decoded = track.decode_frame_with(&frame, &options).unwrap();
+        assert_eq!(&*decoded, b"\x00\x01secret");
+
+        // partitioned: clear, encrypted, clear
+        let mut hidden = b"hidden".to_vec();
+        encrypt(&mut hidden);
+        let partitions = [&[2][..], &1u32.to_be_bytes(), &7u32.to_be_bytes()].concat();
+        let frame = [&[0x03][..], &iv, &partitions, b"[", &hidden, b"]"].concat();
+        let decoded = track.decode_frame_with(&frame, &options).unwrap();
+        assert_eq!(&*decoded, b"\x00\x01[hidden]");
+
+        // a clear frame only has the signal byte
+        let decoded = track.decode_frame_with(b"\x00plain", &options).unwrap();
+        assert_eq!(&*decoded, b"\x00\x01plain");
+
+        assert!(matches!(
+            track.decode_frame(&frame),
+            Err(Error::MissingDecryptionKey(1))
+        ));
+        let truncated = [0x01, 1, 2, 3];
+        assert!(matches!(
+            track.decode_frame_with(&truncated, &options),
+            Err(Error::InvalidEncryptedFrame(1))
+        ));
+        let backwards = [
+            &[0x03][..],
+            &iv,
+            &[2],
+            &6u32.to_be_bytes(),
+            &1u32.to_be_bytes(),
+            b"1234567",
+        ]
+        .concat();
+        assert!(matches!(
+            track.decode_frame_with(&backwards, &options),
+            Err(Error::InvalidEncryptedFrame(1))
+        ));
+    }
+
+    #[test]
+    fn test_content_encodings_decode_order() {
+        let encoding = |order: u64, kind: u64| ContentEncoding {
+            content_encoding_order: ContentEncodingOrder(order),
+            content_encoding_type: ContentEncodingType(kind),
+            ..Default::default()
+        };
+        // compressed, then encrypted: decrypt first, then decompress
+        let mut encodings = ContentEncodings {
+            content_encoding: vec![encoding(0, 0), encoding(1, 1)],
+            ..Default::default()
+        };
+        let order = encodings.in_decode_order().unwrap();
+        assert_eq!(
+            order
+                .iter()
+                .map(|e| *e.content_encoding_type)
+                .collect::<Vec<_>>(),
+            vec![1, 0]
+        );
+
+        encodings.content_encoding.push(encoding(1, 0));
+        assert!(matches!(
+            encodings.in_decode_order(),
+            Err(Error::DuplicateEncodingOrder(1))
+        ));
+    }
+}
@@ -0,0 +1,518 @@
+//! Helpers for working with video settings.
+
+use crate::Error;
+use crate::leaf::{AlphaMode, DisplayUnit, FieldOrder, FlagInterlaced, StereoMode};
+use crate::master::{MasteringMetadata, Projection, Video};
+
+/// A typed view of an enumerated element, converting from and to its raw value.
+/// Values not defined by the specification are kept in `Unknown`.
+macro_rules! kind_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$variant_meta:meta])* $variant:ident = $value:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value not defined by the specification.
+            Unknown(u64),
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    v => Self::Unknown(v),
+                }
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(kind: $name) -> u64 {
+                match kind {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(v) => v,
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use kind_enum;
+
+kind_enum! {
+    /// Stereo-3D layout of a video track, from `StereoMode`.
+    StereoModeKind {
+        /// Not stereoscopic.
+        Mono = 0,
+        /// Side by side, left eye first.
+        SideBySideLeftFirst = 1,
+        /// Top-bottom, right eye first.
+        TopBottomRightFirst = 2,
+        /// Top-bottom, left eye first.
+        TopBottomLeftFirst = 3,
+        /// Checkboard, right eye first.
+        CheckboardRightFirst = 4,
+        /// Checkboard, left eye first.
+        CheckboardLeftFirst = 5,
+        /// Row interleaved, right eye first.
+        RowInterleavedRightFirst = 6,
+        /// Row interleaved, left eye first.
+        RowInterleavedLeftFirst = 7,
+        /// Column interleaved, right eye first.
+        ColumnInterleavedRightFirst = 8,
+        /// Column interleaved, left eye first.
+        ColumnInterleavedLeftFirst = 9,
+        /// Anaglyph, cyan/red.
+        AnaglyphCyanRed = 10,
+        /// Side by side, right eye first.
+        SideBySideRightFirst = 11,
+        /// Anaglyph, green/magenta.
+        AnaglyphGreenMagenta = 12,
+        /// Both eyes laced in one Block, left eye first.
+        BothEyesLacedLeftFirst = 13,
+        /// Both eyes laced in one Block, right eye first.
+        BothEyesLacedRightFirst = 14,
+    }
+}
+
+kind_enum! {
+    /// Whether a video track carries alpha data in its BlockAdditions, from `AlphaMode`.
+    AlphaModeKind {
+        /// No alpha data.
+        None = 0,
+        /// Alpha data in the BlockAdditional with BlockAddID 1.
+        Present = 1,
+    }
+}
+
+kind_enum! {
+    /// How `DisplayWidth` and `DisplayHeight` are interpreted, from `DisplayUnit`.
+    DisplayUnitKind {
+        /// Pixels.
+        Pixels = 0,
+        /// Centimeters.
+        Centimeters = 1,
+        /// Inches.
+        Inches = 2,
+        /// Only the ratio of the dimensions matters.
+        DisplayAspectRatio = 3,
+        /// A unit the file does not specify.
+        Unspecified = 4,
+    }
+}
+
+kind_enum! {
+    /// Interlacing of a video track, from `FlagInterlaced`.
+    InterlaceMode {
+        /// Not known whether the frames are interlaced.
+        Undetermined = 0,
+        /// The frames are interlaced.
+        Interlaced = 1,
+        /// The frames are progressive.
+        Progressive = 2,
+    }
+}
+
+kind_enum! {
+    /// Field ordering of interlaced video frames, from `FieldOrder`.
+    FieldOrderKind {
+        /// Progressive frames, no fields.
+        Progressive = 0,
+        /// Top field displayed first, top field stored first.
+        TopFieldFirst = 1,
+        /// Field ordering not known.
+        Undetermined = 2,
+        /// Bottom field displayed first, bottom field stored first.
+        BottomFieldFirst = 6,
+        /// Bottom field displayed first, top field stored first.
+        BottomFieldFirstSwapped = 9,
+        /// Top field displayed first, bottom field stored first.
+        TopFieldFirstSwapped = 14,
+    }
+}
+
+impl StereoMode {
+    /// The stereo layout this value describes.
+    pub fn kind(&self) -> StereoModeKind {
+        self.0.into()
+    }
+}
+
+impl AlphaMode {
+    /// The alpha mode this value describes.
+    pub fn kind(&self) -> AlphaModeKind {
+        self.0.into()
+    }
+}
+
+impl DisplayUnit {
+    /// The display unit this value describes.
+    pub fn kind(&self) -> DisplayUnitKind {
+        self.0.into()
+    }
+}
+
+impl FlagInterlaced {
+    /// The interlacing this flag describes.
+    pub fn mode(&self) -> InterlaceMode {
+        self.0.into()
+    }
+}
+
+impl FieldOrder {
+    /// The field ordering this value describes.
+    pub fn kind(&self) -> FieldOrderKind {
+        self.0.into()
+    }
+}
+
+impl Video {
+    /// The stereo layout of the video, see [`StereoModeKind`].
+    pub fn stereo_mode_kind(&self) -> StereoModeKind {
+        self.stereo_mode.kind()
+    }
+
+    /// Whether the video carries alpha data, see [`AlphaModeKind`].
+    pub fn alpha_mode_kind(&self) -> AlphaModeKind {
+        self.alpha_mode.kind()
+    }
+
+    /// The unit of the display dimensions, see [`DisplayUnitKind`].
+    pub fn display_unit_kind(&self) -> DisplayUnitKind {
+        self.display_unit.kind()
+    }
+
+    /// The `UncompressedFourCC` of the video, if present and exactly 4 bytes long.
+    pub fn fourcc(&self) -> Option<[u8; 4]> {
+        self.uncompressed_fourcc.as_ref()?[..].try_into().ok()
+    }
+
+    /// The field ordering of the video, or `None` unless `FlagInterlaced` marks it interlaced,
+    /// as `FieldOrder` must then be ignored.
+    pub fn field_order_effective(&self) -> Option<FieldOrderKind> {
+        (self.flag_interlaced.mode() == InterlaceMode::Interlaced).then(|| self.field_order.kind())
+    }
+
+    /// Check that the `PixelCrop*` elements leave some of the picture in both directions.
+    ///
+    /// Returns [`Error::InvalidCrop`] if the left and right crops together are not less than
+    /// the `PixelWidth`, or the top and bottom crops together not less than the `PixelHeight`.
+    /// A video without any crop always passes.
+    pub fn validate_crop(&self) -> crate::Result<()> {
+        let horizontal = (
+            self.pixel_crop_left.saturating_add(*self.pixel_crop_right),
+            *self.pixel_width,
+        );
+        let vertical = (
+            self.pixel_crop_top.saturating_add(*self.pixel_crop_bottom),
+            *self.pixel_height,
+        );
+        for (crop, size) in [horizontal, vertical] {
+            if crop > 0 && crop >= size {
+                return Err(Error::InvalidCrop { crop, size });
+            }
+        }
+        Ok(())
+    }
+
+    /// The display aspect ratio as a reduced `(width, height)` rational, e.g. `(16, 9)`.
+    ///
+    /// With a `DisplayUnit` of 0 (pixels), the display dimensions default to the cropped pixel
+    /// dimensions. With 1 (centimeters), 2 (inches) or 3 (display aspect ratio), both display
+    /// dimensions are required. Returns `None` for an unknown unit or a zero dimension.
+    pub fn display_aspect_ratio(&self) -> Option<(u64, u64)> {
+        let (width, height) = match *self.display_unit {
+            0 => (
+                self.display_width.map_or_else(
+                    || {
+                        self.pixel_width.saturating_sub(
+                            self.pixel_crop_left.saturating_add(*self.pixel_crop_right),
+                        )
+                    },
+                    |w| *w,
+                ),
+                self.display_height.map_or_else(
+                    || {
+                        self.pixel_height.saturating_sub(
+                            self.pixel_crop_top.saturating_add(*self.pixel_crop_bottom),
+                        )
+                    },
+                    |h| *h,
+                ),
+            ),
+            1..=3 => (*self.display_width?, *self.display_height?),
+            _ => return None,
+        };
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let gcd = gcd(width, height);
+        Some((width / gcd, height / gcd))
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Projection {
+    /// The ISOBMFF box fourcc matching the `ProjectionType`, `None` for rectangular or unknown types.
+    fn box_type(&self) -> Option<&'static [u8; 4]> {
+        match *self.projection_type {
+            1 => Some(b"equi"),
+            2 => Some(b"cbmp"),
+            3 => Some(b"mshp"),
+            _ => None,
+        }
+    }
+
+    /// The payload of the private data for the given projection type, if it is the current one.
+    fn private_for(&self, projection_type: u64) -> Option<&[u8]> {
+        (*self.projection_type == projection_type).then_some(self.projection_private.as_deref()?)
+    }
+
+    /// The payload of the ISOBMFF `equi` box, for an equirectangular projection.
+    pub fn equirectangular_box(&self) -> Option<&[u8]> {
+        self.private_for(1)
+    }
+
+    /// The payload of the ISOBMFF `cbmp` box, for a cubemap projection.
+    pub fn cubemap_box(&self) -> Option<&[u8]> {
+        self.private_for(2)
+    }
+
+    /// The payload of the ISOBMFF `mshp` box, for a mesh projection.
+    pub fn mesh_box(&self) -> Option<&[u8]> {
+        self.private_for(3)
+    }
+
+    /// The private data wrapped into a complete ISOBMFF box, with the size and fourcc that
+    /// Matroska leaves out, ready for MP4 tooling.
+    pub fn isobmff_box(&self) -> Option<Vec<u8>> {
+        let box_type = self.box_type()?;
+        let payload = self.projection_private.as_deref()?;
+        let size = u32::try_from(8 + payload.len()).ok()?;
+        Some([&size.to_be_bytes()[..], box_type, payload].concat())
+    }
+}
+
+impl MasteringMetadata {
+    /// The number of chromaticity and luminance values present, out of 10.
+    fn present(&self) -> usize {
+        [
+            self.primary_r_chromaticity_x.is_some(),
+            self.primary_r_chromaticity_y.is_some(),
+            self.primary_g_chromaticity_x.is_some(),
+            self.primary_g_chromaticity_y.is_some(),
+            self.primary_b_chromaticity_x.is_some(),
+            self.primary_b_chromaticity_y.is_some(),
+            self.white_point_chromaticity_x.is_some(),
+            self.white_point_chromaticity_y.is_some(),
+            self.luminance_max.is_some(),
+            self.luminance_min.is_some(),
+        ]
+        .into_iter()
+        .filter(|&present| present)
+        .count()
+    }
+
+    /// Whether the SMPTE 2086 mastering display is fully described: the three primaries and the
+    /// white point chromaticities, and the minimum and maximum luminance.
+    pub fn is_complete(&self) -> bool {
+        self.present() == 10
+    }
+
+    /// Check that the values are either all present or all absent, as a partial set confuses players.
+    ///
+    /// A partial set gives [`Error::IncompleteMasteringMetadata`].
+    pub fn validate(&self) -> crate::Result<()> {
+        match self.present() {
+            0 | 10 => Ok(()),
+            present => Err(Error::IncompleteMasteringMetadata { present }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldOrderKind, InterlaceMode};
+    use crate::leaf::*;
+    use crate::master::Video;
+
+    #[test]
+    fn test_display_aspect_ratio() {
+        let mut video = Video {
+            pixel_width: PixelWidth(1920),
+            pixel_height: PixelHeight(1088),
+            pixel_crop_bottom: PixelCropBottom(8),
+            ..Default::default()
+        };
+        assert_eq!(video.display_aspect_ratio(), Some((16, 9)));
+
+        // anamorphic, stretched to 2.35:1
+        video.display_width = Some(DisplayWidth(2538));
+        video.display_height = Some(DisplayHeight(1080));
+        assert_eq!(video.display_aspect_ratio(), Some((47, 20)));
+
+        video.display_unit = DisplayUnit(3);
+        video.display_width = Some(DisplayWidth(4));
+        video.display_height = Some(DisplayHeight(3));
+        assert_eq!(video.display_aspect_ratio(), Some((4, 3)));
+        video.display_height = None;
+        assert_eq!(video.display_aspect_ratio(), None);
+
+        video.display_unit = DisplayUnit(4);
+        assert_eq!(video.display_aspect_ratio(), None);
+
+        // crops adding up past u64::MAX leave nothing to display, without overflowing
+        let video = Video {
+            pixel_width: PixelWidth(1920),
+            pixel_height: PixelHeight(1080),
+            pixel_crop_left: PixelCropLeft(u64::MAX),
+            pixel_crop_right: PixelCropRight(1),
+            ..Default::default()
+        };
+        assert_eq!(video.display_aspect_ratio(), None);
+    }
+
+    #[test]
+    fn test_validate_crop() {
+        let mut video = Video {
+            pixel_width: PixelWidth(640),
+            pixel_height: PixelHeight(480),
+            pixel_crop_left: PixelCropLeft(300),
+            pixel_crop_right: PixelCropRight(300),
+            ..Default::default()
+        };
+        assert!(video.validate_crop().is_ok());
+
+        video.pixel_crop_right = PixelCropRight(340);
+        assert!(matches!(
+            video.validate_crop(),
+            Err(crate::Error::InvalidCrop {
+                crop: 640,
+                size: 640
+            })
+        ));
+
+        video.pixel_crop_right = PixelCropRight(0);
+        video.pixel_crop_bottom = PixelCropBottom(u64::MAX);
+        video.pixel_crop_top = PixelCropTop(1);
+        assert!(video.validate_crop().is_err());
+    }
+
+    #[test]
+    fn test_mastering_metadata_completeness() {
+        use crate::master::MasteringMetadata;
+
+        let mut metadata = MasteringMetadata::default();
+        assert!(!metadata.is_complete());
+        metadata.validate().unwrap();
+
+        metadata.primary_r_chromaticity_x = Some(PrimaryRChromaticityX(0.708));
+        metadata.primary_r_chromaticity_y = Some(PrimaryRChromaticityY(0.292));
+        assert!(!metadata.is_complete());
+        assert!(matches!(
+            metadata.validate(),
+            Err(crate::Error::IncompleteMasteringMetadata { present: 2 })
+        ));
+
+        // BT.2020 primaries, D65 white point
+        let metadata = MasteringMetadata {
+            primary_g_chromaticity_x: Some(PrimaryGChromaticityX(0.170)),
+            primary_g_chromaticity_y: Some(PrimaryGChromaticityY(0.797)),
+            primary_b_chromaticity_x: Some(PrimaryBChromaticityX(0.131)),
+            primary_b_chromaticity_y: Some(PrimaryBChromaticityY(0.046)),
+            white_point_chromaticity_x: Some(WhitePointChromaticityX(0.3127)),
+            white_point_chromaticity_y: Some(WhitePointChromaticityY(0.3290)),
+            luminance_max: Some(LuminanceMax(1000.0)),
+            luminance_min: Some(LuminanceMin(0.0001)),
+            ..metadata
+        };
+        assert!(metadata.is_complete());
+        metadata.validate().unwrap();
+    }
+
+    #[test]
+    fn test_field_order_effective() {
+        let mut video = Video {
+            flag_interlaced: FlagInterlaced(2),
+            field_order: FieldOrder(1),
+            ..Default::default()
+        };
+        assert_eq!(video.flag_interlaced.mode(), InterlaceMode::Progressive);
+        // ignored for a progressive track
+        assert_eq!(video.field_order_effective(), None);
+
+        video.flag_interlaced = FlagInterlaced(1);
+        assert_eq!(
+            video.field_order_effective(),
+            Some(FieldOrderKind::TopFieldFirst)
+        );
+        video.field_order = FieldOrder(9);
+        assert_eq!(
+            video.field_order_effective(),
+            Some(FieldOrderKind::BottomFieldFirstSwapped)
+        );
+
+        video.flag_interlaced = FlagInterlaced(0);
+        assert_eq!(video.field_order_effective(), None);
+        assert_eq!(FieldOrder(3).kind(), FieldOrderKind::Unknown(3));
+    }
+
+    #[test]
+    fn test_projection_boxes() {
+        use crate::master::Projection;
+        use bytes::Bytes;
+
+        // FullBox version and flags, then the four bounds
+        let payload: Vec<u8> = [0u8; 4].into_iter().chain([0x11; 16]).collect();
+        let mut projection = Projection {
+            projection_type: ProjectionType(1),
+            projection_private: Some(ProjectionPrivate(Bytes::from(payload.clone()))),
+            ..Default::default()
+        };
+        assert_eq!(projection.equirectangular_box(), Some(&payload[..]));
+        assert_eq!(projection.cubemap_box(), None);
+
+        let full = projection.isobmff_box().unwrap();
+        assert_eq!(&full[..4], 28u32.to_be_bytes());
+        assert_eq!(&full[4..8], b"equi");
+        assert_eq!(&full[8..], &payload[..]);
+
+        projection.projection_type = ProjectionType(0);
+        assert_eq!(projection.equirectangular_box(), None);
+        assert_eq!(projection.isobmff_box(), None);
+    }
+
+    #[test]
+    fn test_kind_enums() {
+        use super::{AlphaModeKind, DisplayUnitKind, StereoModeKind};
+
+        let mut video = Video::default();
+        assert_eq!(video.stereo_mode_kind(), StereoModeKind::Mono);
+        assert_eq!(video.alpha_mode_kind(), AlphaModeKind::None);
+        assert_eq!(video.display_unit_kind(), DisplayUnitKind::Pixels);
+
+        video.stereo_mode = StereoMode(1);
+        assert_eq!(
+            video.stereo_mode_kind(),
+            StereoModeKind::SideBySideLeftFirst
+        );
+        video.stereo_mode = StereoMode(14);
+        assert_eq!(
+            video.stereo_mode_kind(),
+            StereoModeKind::BothEyesLacedRightFirst
+        );
+        video.stereo_mode = StereoMode(15);
+        assert_eq!(video.stereo_mode_kind(), StereoModeKind::Unknown(15));
+
+        for value in [0, 3, 10, 15, 99] {
+            assert_eq!(u64::from(StereoModeKind::from(value)), value);
+        }
+        assert_eq!(u64::from(DisplayUnitKind::Unspecified), 4);
+        assert_eq!(AlphaModeKind::from(1), AlphaModeKind::Present);
+    }
+}
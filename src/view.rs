@@ -2,9 +2,23 @@
 
 use std::mem::take;
 
-use crate::element::Element;
+use crate::element::{DecodeOptions, Element};
 use crate::master::*;
 
+/// IDs of the elements that end an unknown element of unknown size at the Segment level.
+const TOP_LEVEL_IDS: [crate::base::VInt64; 10] = [
+    SeekHead::ID,
+    Info::ID,
+    Tracks::ID,
+    Cues::ID,
+    Attachments::ID,
+    Chapters::ID,
+    Tags::ID,
+    Cluster::ID,
+    Segment::ID,
+    Ebml::ID,
+];
+
 /// View of a Matroska file, parsing the EBML and Segment headers, but not loading Clusters.
 #[derive(Debug, Clone, PartialEq)]
 pub struct MatroskaView {
@@ -53,8 +67,149 @@ impl MatroskaView {
         // Parse all segments in the file
         let segments = SegmentView::new_async(reader).await?;
 
+        // At least one segment is required
+        if segments.is_empty() {
+            return Err(crate::Error::MissingElement(Segment::ID));
+        }
+
         Ok(MatroskaView { ebml, segments })
     }
+
+    /// Describe the file in the layout of `mkvmerge -J`, for tools that already consume it.
+    ///
+    /// Like mkvmerge, only the first Segment is described. Track and attachment ids are
+    /// their 0-based positions, and the `codec` of a track is its `CodecID`.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn identify_json(&self) -> serde_json::Value {
+        use serde_json::{Value, json};
+
+        // a view built by hand may have no Segment, described as an empty one
+        let segment = self.segments.first();
+        let container_type = match self.ebml.doc_type.as_ref().map(|d| d.0.as_str()) {
+            Some("webm") => "WebM",
+            _ => "Matroska",
+        };
+        let properties = segment.map_or_else(
+            || json!({}),
+            |segment| {
+                let info = &segment.info;
+                json!({
+                    "title": info.title.as_ref().map(|t| &t.0),
+                    "muxing_application": &info.muxing_app.0,
+                    "writing_application": &info.writing_app.0,
+                    "timestamp_scale": *info.timestamp_scale,
+                    "duration": info.duration.map(|d| (*d * *info.timestamp_scale as f64) as u64),
+                })
+            },
+        );
+        let container = json!({
+            "type": container_type,
+            "recognized": true,
+            "supported": true,
+            "properties": properties,
+        });
+
+        let tracks = segment
+            .iter()
+            .flat_map(|s| &s.tracks)
+            .flat_map(|t| &t.track_entry);
+        let tracks = tracks
+            .enumerate()
+            .map(|(id, track)| {
+                let track_type = match *track.track_type {
+                    1 => "video",
+                    2 => "audio",
+                    17 => "subtitles",
+                    _ => "unknown",
+                };
+                let mut properties = json!({
+                    "number": *track.track_number,
+                    "uid": *track.track_uid,
+                    "codec_id": &track.codec_id.0,
+                    "language": &track.language.0,
+                    "language_ietf": track.language_bcp47.as_ref().map(|l| &l.0),
+                    "track_name": track.name.as_ref().map(|n| &n.0),
+                    "enabled_track": *track.flag_enabled != 0,
+                    "default_track": *track.flag_default != 0,
+                    "forced_track": *track.flag_forced != 0,
+                });
+                if let Some(video) = &track.video {
+                    properties["pixel_dimensions"] =
+                        format!("{}x{}", *video.pixel_width, *video.pixel_height).into();
+                    if let (Some(w), Some(h)) = (video.display_width, video.display_height) {
+                        properties["display_dimensions"] = format!("{}x{}", *w, *h).into();
+                    }
+                }
+                if let Some(audio) = &track.audio {
+                    properties["audio_sampling_frequency"] = (*audio.sampling_frequency).into();
+                    properties["audio_channels"] = (*audio.channels).into();
+                    if let Some(bit_depth) = audio.bit_depth {
+                        properties["audio_bits_per_sample"] = (*bit_depth).into();
+                    }
+                }
+                json!({
+                    "id": id,
+                    "type": track_type,
+                    "codec": &track.codec_id.0,
+                    "properties": properties,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let attachments = segment
+            .iter()
+            .flat_map(|s| &s.attachments)
+            .enumerate()
+            .map(|(id, attachment)| {
+                let file = &attachment.file;
+                json!({
+                    "id": id,
+                    "file_name": &file.file_name.0,
+                    "content_type": &file.file_media_type.0,
+                    "description": file.file_description.as_ref().map(|d| &d.0),
+                    "size": attachment.data_size,
+                    "properties": { "uid": *file.file_uid },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let chapters = segment
+            .iter()
+            .flat_map(|s| &s.chapters)
+            .flat_map(|c| &c.edition_entry)
+            .map(|edition| json!({ "num_entries": edition.chapter_atom.len() }))
+            .collect::<Vec<_>>();
+
+        let mut identify = serde_json::Map::new();
+        identify.insert("container".into(), container);
+        identify.insert("tracks".into(), Value::Array(tracks));
+        identify.insert("attachments".into(), Value::Array(attachments));
+        identify.insert("chapters".into(), Value::Array(chapters));
+        Value::Object(identify)
+    }
+
+    /// Copy the data of the attachment with the given `FileUID` to a writer,
+    /// without loading the attachment into memory.
+    ///
+    /// Returns the number of bytes written.
+    pub fn extract_attachment<R, W>(
+        &self,
+        reader: &mut R,
+        file_uid: u64,
+        out: &mut W,
+    ) -> crate::Result<u64>
+    where
+        R: std::io::Read + std::io::Seek + ?Sized,
+        W: std::io::Write + ?Sized,
+    {
+        let segment = self
+            .segments
+            .iter()
+            .find(|s| s.attachments.iter().any(|a| *a.file.file_uid == file_uid))
+            .ok_or(crate::Error::AttachmentNotFound(file_uid))?;
+        segment.extract_attachment(reader, file_uid, out)
+    }
 }
 
 /// View of a Segment, parsing the Segment header, but not loading Clusters.
@@ -68,8 +223,9 @@ pub struct SegmentView {
     pub tracks: Option<Tracks>,
     /// A Top-Level Element to speed seeking access. All entries are local to the Segment. This Element **SHOULD** be set when the Segment is not transmitted as a live stream (see #livestreaming).
     pub cues: Option<Cues>,
-    /// Contain attached files.
-    pub attachments: Option<Attachments>,
+    /// Attached files, without their data, which stays in the file until it is extracted with
+    /// [`SegmentView::extract_attachment`].
+    pub attachments: Vec<AttachmentView>,
     /// A system to define basic menus and partition data. For more detailed information, look at the Chapters explanation in chapters.
     pub chapters: Option<Chapters>,
     /// Element containing metadata describing Tracks, Editions, Chapters, Attachments, or the Segment as a whole. A list of valid tags can be found in [Matroska tagging RFC](https://www.matroska.org/technical/tagging.html).
@@ -78,12 +234,167 @@ pub struct SegmentView {
     pub segment_data_position: u64,
     /// The position of the first Cluster in the Segment. 0 if no Cluster found.
     pub first_cluster_position: u64,
+    /// The position of the Attachments element in the Segment, if present.
+    pub attachments_position: Option<u64>,
+}
+
+/// An attached file of a [`SegmentView`], whose data is left in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachmentView {
+    /// The attached file, with an empty `FileData`.
+    pub file: AttachedFile,
+    /// The position of the `FileData` body in the file.
+    pub data_position: u64,
+    /// The size of the `FileData` body, in bytes.
+    pub data_size: u64,
+}
+
+/// Read the element with the given header, decoding its body with the given options.
+fn read_element_with<T: Element, R>(
+    header: &crate::base::Header,
+    reader: &mut R,
+    options: &DecodeOptions,
+) -> crate::Result<T>
+where
+    R: std::io::Read + ?Sized,
+{
+    let body = header.read_body(reader)?;
+    T::decode_body_with(&mut &body[..], options)
+}
+
+/// Read the element with the given header, decoding its body with the given options.
+#[cfg(feature = "tokio")]
+async fn async_read_element_with<T: Element, R>(
+    header: &crate::base::Header,
+    reader: &mut R,
+    options: &DecodeOptions,
+) -> crate::Result<T>
+where
+    R: tokio::io::AsyncRead + Unpin + ?Sized,
+{
+    let body = header.read_body_tokio(reader).await?;
+    T::decode_body_with(&mut &body[..], options)
+}
+
+/// Read the attached files of the Attachments element with the given header, skipping their data.
+fn read_attachments<R>(
+    header: &crate::base::Header,
+    reader: &mut R,
+) -> crate::Result<Vec<AttachmentView>>
+where
+    R: std::io::Read + std::io::Seek + ?Sized,
+{
+    use crate::Encode;
+    use crate::base::Header;
+    use crate::io::blocking_impl::*;
+    use crate::leaf::FileData;
+
+    let end = reader.stream_position()? + *header.size;
+    let mut files = vec![];
+    while reader.stream_position()? < end {
+        let file_header = Header::read_from(reader)?;
+        if file_header.id != AttachedFile::ID {
+            file_header.skip(reader)?;
+            continue;
+        }
+        let file_end = reader.stream_position()? + *file_header.size;
+        let mut body = vec![];
+        let mut data = None;
+        while reader.stream_position()? < file_end {
+            let header = Header::read_from(reader)?;
+            if header.id == FileData::ID {
+                data = Some((reader.stream_position()?, *header.size));
+                header.skip(reader)?;
+            } else {
+                header.encode(&mut body)?;
+                body.extend(header.read_body(reader)?);
+            }
+        }
+        files.push(attachment_view(body, data)?);
+    }
+    Ok(files)
+}
+
+/// Read the attached files of the Attachments element with the given header, skipping their data.
+#[cfg(feature = "tokio")]
+async fn read_attachments_async<R>(
+    header: &crate::base::Header,
+    reader: &mut R,
+) -> crate::Result<Vec<AttachmentView>>
+where
+    R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin + ?Sized,
+{
+    use crate::Encode;
+    use crate::base::Header;
+    use crate::io::tokio_impl::*;
+    use crate::leaf::FileData;
+    use tokio::io::AsyncSeekExt;
+
+    let end = reader.stream_position().await? + *header.size;
+    let mut files = vec![];
+    while reader.stream_position().await? < end {
+        let file_header = Header::async_read_from(reader).await?;
+        if file_header.id != AttachedFile::ID {
+            reader
+                .seek(std::io::SeekFrom::Current(*file_header.size as i64))
+                .await?;
+            continue;
+        }
+        let file_end = reader.stream_position().await? + *file_header.size;
+        let mut body = vec![];
+        let mut data = None;
+        while reader.stream_position().await? < file_end {
+            let header = Header::async_read_from(reader).await?;
+            if header.id == FileData::ID {
+                let position = reader.stream_position().await?;
+                data = Some((position, *header.size));
+                reader
+                    .seek(std::io::SeekFrom::Current(*header.size as i64))
+                    .await?;
+            } else {
+                header.encode(&mut body)?;
+                body.extend(header.read_body_tokio(reader).await?);
+            }
+        }
+        files.push(attachment_view(body, data)?);
+    }
+    Ok(files)
+}
+
+/// Decode an attached file from the `body` of its children but `FileData`, found at `data`.
+fn attachment_view(mut body: Vec<u8>, data: Option<(u64, u64)>) -> crate::Result<AttachmentView> {
+    use crate::Encode;
+    use crate::leaf::FileData;
+
+    let (data_position, data_size) = data.ok_or(crate::Error::MissingElement(FileData::ID))?;
+    // an empty FileData stands in for the data left in the file
+    FileData::default().encode(&mut body)?;
+    let file = AttachedFile::decode_body(&mut &body[..])?;
+    Ok(AttachmentView {
+        file,
+        data_position,
+        data_size,
+    })
 }
 
 impl SegmentView {
     /// Create a new SegmentView by parsing the Segment header and metadata elements,
     /// but skipping Cluster data to avoid loading it into memory.
+    ///
+    /// Returns no SegmentView if the reader is already at its end.
     pub fn new<R>(reader: &mut R) -> crate::Result<Vec<Self>>
+    where
+        R: std::io::Read + std::io::Seek + ?Sized,
+    {
+        Self::new_with(reader, &DecodeOptions::default())
+    }
+
+    /// Create SegmentViews as [`SegmentView::new`], decoding the metadata elements with the
+    /// given options.
+    ///
+    /// Skipped unknown elements, at the Segment level as well as in the metadata elements, are
+    /// reported to [`DecodeOptions::on_unknown`] when set, instead of being logged.
+    pub fn new_with<R>(reader: &mut R, options: &DecodeOptions) -> crate::Result<Vec<Self>>
     where
         R: std::io::Read + std::io::Seek + ?Sized,
     {
@@ -92,6 +403,12 @@ impl SegmentView {
         use crate::io::blocking_impl::*;
         use std::io::SeekFrom;
 
+        // Nothing after the EBML header, let the caller report the missing Segment
+        if reader.read(&mut [0u8])? == 0 {
+            return Ok(out);
+        }
+        reader.seek(SeekFrom::Current(-1))?;
+
         // Read the Segment header
         let segment_header = crate::base::Header::read_from(reader)?;
         if segment_header.id != Segment::ID {
@@ -104,18 +421,21 @@ impl SegmentView {
         let mut info = None;
         let mut tracks = None;
         let mut cues = None;
-        let mut attachments = None;
+        let mut attachments = Vec::new();
         let mut chapters = None;
         let mut tags = Vec::new();
         let mut first_cluster_position = 0;
+        let mut attachments_position = None;
 
         // Parse segment elements
         loop {
             use crate::base::Header;
 
             let current_position = reader.stream_position()?;
-            let Ok(header) = Header::read_from(reader) else {
-                break;
+            let header = match Header::read_from(reader) {
+                Ok(header) => header,
+                Err(e) if e.is_eof() => break,
+                Err(e) => return Err(e),
             };
             if header.id == Cluster::ID && first_cluster_position == 0 {
                 first_cluster_position = current_position;
@@ -123,24 +443,36 @@ impl SegmentView {
 
             // Check if we've reached the end of the segment
             match header.id {
-                SeekHead::ID => seek_head.push(SeekHead::read_element(&header, reader)?),
-                Info::ID => info = Some(Info::read_element(&header, reader)?),
-                Tracks::ID => tracks = Some(Tracks::read_element(&header, reader)?),
-                Cues::ID => cues = Some(Cues::read_element(&header, reader)?),
-                Attachments::ID => attachments = Some(Attachments::read_element(&header, reader)?),
-                Chapters::ID => chapters = Some(Chapters::read_element(&header, reader)?),
-                Tags::ID => tags.push(Tags::read_element(&header, reader)?),
+                SeekHead::ID => {
+                    seek_head.push(read_element_with::<SeekHead, _>(&header, reader, options)?)
+                }
+                Info::ID => info = Some(read_element_with::<Info, _>(&header, reader, options)?),
+                Tracks::ID => {
+                    tracks = Some(read_element_with::<Tracks, _>(&header, reader, options)?)
+                }
+                Cues::ID => cues = Some(read_element_with::<Cues, _>(&header, reader, options)?),
+                Attachments::ID => {
+                    attachments_position = Some(current_position);
+                    attachments = read_attachments(&header, reader)?;
+                }
+                Chapters::ID => {
+                    chapters = Some(read_element_with::<Chapters, _>(&header, reader, options)?)
+                }
+                Tags::ID => tags.push(read_element_with::<Tags, _>(&header, reader, options)?),
                 Cluster::ID => {
                     // try to skip, or else break
                     use crate::base::VInt64;
                     let mut seeks: Vec<(VInt64, u64)> = seek_head
                         .iter()
                         .flat_map(|sh| {
-                            sh.seek.iter().flat_map(|s| {
+                            sh.iter().flat_map(|s| {
                                 let mut id = &s.seek_id[..];
                                 let a = VInt64::read_from(&mut id);
                                 match a {
-                                    Ok(v) => Some((v, *s.seek_position + segment_data_position)),
+                                    Ok(v) => Some((
+                                        v,
+                                        s.seek_position.to_absolute(segment_data_position),
+                                    )),
                                     Err(e) => {
                                         log::warn!("Failed to read seek_id as VInt: {e}, skip...");
                                         None
@@ -173,20 +505,24 @@ impl SegmentView {
                         info: info.take().ok_or(crate::Error::MissingElement(Info::ID))?,
                         tracks: tracks.take(),
                         cues: cues.take(),
-                        attachments: attachments.take(),
+                        attachments: take(&mut attachments),
                         chapters: chapters.take(),
                         tags: take(&mut tags),
                         first_cluster_position: take(&mut first_cluster_position),
+                        attachments_position: attachments_position.take(),
                         segment_data_position: take(&mut segment_data_position),
                     });
                     segment_data_position = reader.stream_position()?;
                 }
+                _ if header.size.is_unknown => {
+                    // An unknown element of unknown size ends at the next Top-Level Element
+                    header.skip_to_sibling(reader, &TOP_LEVEL_IDS)?;
+                    skipped_unknown(&header, Segment::ID, options);
+                }
                 _ => {
-                    use log::warn;
-                    use std::io::Read;
                     // Skip unknown elements, here we read and discard the data for efficiency
-                    std::io::copy(&mut reader.take(*header.size), &mut std::io::sink())?;
-                    warn!("Skipped unknown element with ID: {}", header.id);
+                    header.skip_read(reader)?;
+                    skipped_unknown(&header, Segment::ID, options);
                 }
             }
         }
@@ -204,22 +540,44 @@ impl SegmentView {
             tags,
             first_cluster_position,
             segment_data_position,
+            attachments_position,
         });
         Ok(out)
     }
 
     /// Create a new SegmentView by parsing the Segment header and metadata elements,
     /// but skipping Cluster data to avoid loading it into memory.
+    ///
+    /// Returns no SegmentView if the reader is already at its end.
     #[cfg(feature = "tokio")]
     #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
     pub async fn new_async<R>(reader: &mut R) -> crate::Result<Vec<Self>>
+    where
+        R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin + ?Sized,
+    {
+        Self::new_async_with(reader, &DecodeOptions::default()).await
+    }
+
+    /// Create SegmentViews as [`SegmentView::new_with`], reading asynchronously.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub async fn new_async_with<R>(
+        reader: &mut R,
+        options: &DecodeOptions,
+    ) -> crate::Result<Vec<Self>>
     where
         R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin + ?Sized,
     {
         let mut out = vec![];
 
         use crate::io::tokio_impl::*;
-        use tokio::io::AsyncSeekExt;
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+        // Nothing after the EBML header, let the caller report the missing Segment
+        if reader.read(&mut [0u8]).await? == 0 {
+            return Ok(out);
+        }
+        reader.seek(std::io::SeekFrom::Current(-1)).await?;
 
         // Read the Segment header
         let segment_header = crate::base::Header::async_read_from(reader).await?;
@@ -233,18 +591,21 @@ impl SegmentView {
         let mut info = None;
         let mut tracks = None;
         let mut cues = None;
-        let mut attachments = None;
+        let mut attachments = Vec::new();
         let mut chapters = None;
         let mut tags = Vec::new();
         let mut first_cluster_position = 0;
+        let mut attachments_position = None;
 
         // Parse segment elements
         loop {
             use crate::base::Header;
 
             let current_position = reader.stream_position().await?;
-            let Ok(header) = Header::async_read_from(reader).await else {
-                break;
+            let header = match Header::async_read_from(reader).await {
+                Ok(header) => header,
+                Err(e) if e.is_eof() => break,
+                Err(e) => return Err(e),
             };
             if header.id == Cluster::ID && first_cluster_position == 0 {
                 first_cluster_position = current_position;
@@ -252,32 +613,46 @@ impl SegmentView {
 
             // Check if we've reached the end of the segment
             match header.id {
-                SeekHead::ID => {
-                    seek_head.push(SeekHead::async_read_element(&header, reader).await?)
+                SeekHead::ID => seek_head
+                    .push(async_read_element_with::<SeekHead, _>(&header, reader, options).await?),
+                Info::ID => {
+                    info = Some(async_read_element_with::<Info, _>(&header, reader, options).await?)
+                }
+                Tracks::ID => {
+                    tracks =
+                        Some(async_read_element_with::<Tracks, _>(&header, reader, options).await?)
+                }
+                Cues::ID => {
+                    cues = Some(async_read_element_with::<Cues, _>(&header, reader, options).await?)
                 }
-                Info::ID => info = Some(Info::async_read_element(&header, reader).await?),
-                Tracks::ID => tracks = Some(Tracks::async_read_element(&header, reader).await?),
-                Cues::ID => cues = Some(Cues::async_read_element(&header, reader).await?),
                 Attachments::ID => {
-                    attachments = Some(Attachments::async_read_element(&header, reader).await?)
+                    attachments_position = Some(current_position);
+                    attachments = read_attachments_async(&header, reader).await?;
                 }
                 Chapters::ID => {
-                    chapters = Some(Chapters::async_read_element(&header, reader).await?)
+                    chapters = Some(
+                        async_read_element_with::<Chapters, _>(&header, reader, options).await?,
+                    )
+                }
+                Tags::ID => {
+                    tags.push(async_read_element_with::<Tags, _>(&header, reader, options).await?)
                 }
-                Tags::ID => tags.push(Tags::async_read_element(&header, reader).await?),
                 Cluster::ID => {
                     // try to skip, or else break
                     use crate::base::VInt64;
                     let mut seeks: Vec<(VInt64, u64)> = seek_head
                         .iter()
                         .flat_map(|sh| {
-                            sh.seek.iter().flat_map(|s| {
+                            sh.iter().flat_map(|s| {
                                 use crate::io::blocking_impl::ReadFrom;
 
                                 let mut id = &s.seek_id[..];
                                 let a = VInt64::read_from(&mut id);
                                 match a {
-                                    Ok(v) => Some((v, *s.seek_position + segment_data_position)),
+                                    Ok(v) => Some((
+                                        v,
+                                        s.seek_position.to_absolute(segment_data_position),
+                                    )),
                                     Err(e) => {
                                         log::warn!("Failed to read seek_id as VInt: {e}, skip...");
                                         None
@@ -310,20 +685,25 @@ impl SegmentView {
                         info: info.take().ok_or(crate::Error::MissingElement(Info::ID))?,
                         tracks: tracks.take(),
                         cues: cues.take(),
-                        attachments: attachments.take(),
+                        attachments: take(&mut attachments),
                         chapters: chapters.take(),
                         tags: take(&mut tags),
                         first_cluster_position: take(&mut first_cluster_position),
+                        attachments_position: attachments_position.take(),
                         segment_data_position: take(&mut segment_data_position),
                     });
                     segment_data_position = reader.stream_position().await?;
                 }
+                _ if header.size.is_unknown => {
+                    // An unknown element of unknown size ends at the next Top-Level Element
+                    header.skip_to_sibling_tokio(reader, &TOP_LEVEL_IDS).await?;
+                    skipped_unknown(&header, Segment::ID, options);
+                }
                 _ => {
-                    use log::warn;
                     use tokio::io::AsyncReadExt;
                     // Skip unknown elements, here we read and discard the data for efficiency
                     tokio::io::copy(&mut reader.take(*header.size), &mut tokio::io::sink()).await?;
-                    warn!("Skipped unknown element with ID: {}", header.id);
+                    skipped_unknown(&header, Segment::ID, options);
                 }
             }
         }
@@ -341,7 +721,223 @@ impl SegmentView {
             tags,
             first_cluster_position,
             segment_data_position,
+            attachments_position,
         });
         Ok(out)
     }
+
+    /// Copy the data of the attachment with the given `FileUID` to a writer,
+    /// without loading the attachment into memory.
+    ///
+    /// The data is copied directly from the position recorded in [`SegmentView::attachments`]
+    /// when the view was created. Returns the number of bytes written.
+    pub fn extract_attachment<R, W>(
+        &self,
+        reader: &mut R,
+        file_uid: u64,
+        out: &mut W,
+    ) -> crate::Result<u64>
+    where
+        R: std::io::Read + std::io::Seek + ?Sized,
+        W: std::io::Write + ?Sized,
+    {
+        use std::io::{Read, SeekFrom};
+
+        let attachment = self
+            .attachments
+            .iter()
+            .find(|a| *a.file.file_uid == file_uid)
+            .ok_or(crate::Error::AttachmentNotFound(file_uid))?;
+        let size = attachment.data_size;
+        reader.seek(SeekFrom::Start(attachment.data_position))?;
+        let n = std::io::copy(&mut reader.take(size), out)?;
+        if n != size {
+            return Err(crate::Error::try_get_error(size as usize, n as usize));
+        }
+        Ok(n)
+    }
+
+    /// SHA-256 digest of the Segment metadata: Info, Tracks, Chapters and Tags.
+    ///
+    /// CRC-32 and Void elements are ignored, so identical metadata yields identical digests
+    /// across remuxes, which makes it suitable as a cache key.
+    pub fn metadata_digest(&self) -> crate::Result<[u8; 32]> {
+        use crate::Encode;
+        use sha2::{Digest, Sha256};
+
+        let mut buf = vec![];
+        let mut info = self.info.clone();
+        info.strip_padding();
+        info.encode(&mut buf)?;
+        let mut tracks = self.tracks.clone();
+        tracks.iter_mut().for_each(Element::strip_padding);
+        tracks.encode(&mut buf)?;
+        let mut chapters = self.chapters.clone();
+        chapters.iter_mut().for_each(Element::strip_padding);
+        chapters.encode(&mut buf)?;
+        let mut tags = self.tags.clone();
+        tags.iter_mut().for_each(Element::strip_padding);
+        tags.encode(&mut buf)?;
+
+        Ok(Sha256::digest(&buf).into())
+    }
+
+    /// Byte ranges of the Clusters of this Segment within `data`, the whole file in memory.
+    ///
+    /// Starting at [`SegmentView::first_cluster_position`], only element headers are read to
+    /// find where each Cluster starts and ends. Other Top-Level Elements are skipped, and the scan
+    /// stops at the next Segment or at the end of `data`.
+    pub fn cluster_ranges(&self, data: &[u8]) -> crate::Result<Vec<std::ops::Range<usize>>> {
+        use crate::Decode;
+        use crate::base::Header;
+
+        let mut ranges = vec![];
+        if self.first_cluster_position == 0 {
+            return Ok(ranges);
+        }
+        let mut position = self.first_cluster_position as usize;
+        while position < data.len() {
+            let mut buf = &data[position..];
+            let header = Header::decode(&mut buf)?;
+            if header.id == Segment::ID {
+                break;
+            }
+            if header.size.is_unknown {
+                return Err(crate::Error::ElementBodySizeUnknown(header.id));
+            }
+            let end = data.len() - buf.len() + *header.size as usize;
+            if end > data.len() {
+                return Err(crate::Error::try_get_error(
+                    end - position,
+                    data.len() - position,
+                ));
+            }
+            if header.id == Cluster::ID {
+                ranges.push(position..end);
+            }
+            position = end;
+        }
+        Ok(ranges)
+    }
+
+    /// A seek index of `(timestamp, position)` pairs, with the timestamp in ticks and the absolute
+    /// position in the file of the Cluster to start reading from.
+    ///
+    /// The index comes from the Cues when present. Otherwise the Clusters are scanned from
+    /// [`SegmentView::first_cluster_position`], reading only their `Timestamp` and skipping the
+    /// rest, until the next Segment or the end of the file.
+    pub fn build_index<R>(&self, reader: &mut R) -> crate::Result<Vec<(i64, u64)>>
+    where
+        R: std::io::Read + std::io::Seek + ?Sized,
+    {
+        self.build_index_with(reader, &DecodeOptions::default())
+    }
+
+    /// Like [`SegmentView::build_index`], also checking the `Position` of the scanned Clusters.
+    ///
+    /// A Cluster whose `Position` differs from the offset it was found at gives
+    /// [`Error::PositionMismatch`](crate::Error::PositionMismatch) in strict mode, otherwise it is
+    /// only logged. Only the children before the first block are looked at.
+    pub fn build_index_with<R>(
+        &self,
+        reader: &mut R,
+        options: &DecodeOptions,
+    ) -> crate::Result<Vec<(i64, u64)>>
+    where
+        R: std::io::Read + std::io::Seek + ?Sized,
+    {
+        use crate::base::Header;
+        use crate::io::blocking_impl::*;
+        use crate::leaf::{Position, SimpleBlock, Timestamp};
+        use std::io::SeekFrom;
+
+        if let Some(cues) = &self.cues {
+            return Ok(cues
+                .cue_point
+                .iter()
+                .filter_map(|point| {
+                    let positions = point.cue_track_positions.first()?;
+                    Some((
+                        *point.cue_time as i64,
+                        positions
+                            .cue_cluster_position
+                            .to_absolute(self.segment_data_position),
+                    ))
+                })
+                .collect());
+        }
+
+        let mut index = vec![];
+        if self.first_cluster_position == 0 {
+            return Ok(index);
+        }
+        reader.seek(SeekFrom::Start(self.first_cluster_position))?;
+        loop {
+            let position = reader.stream_position()?;
+            let header = match Header::read_from(reader) {
+                Ok(header) => header,
+                Err(e) if e.is_eof() => break,
+                Err(e) => return Err(e),
+            };
+            if header.id == Segment::ID {
+                break;
+            }
+            if header.size.is_unknown {
+                return Err(crate::Error::ElementBodySizeUnknown(header.id));
+            }
+            let end = reader.stream_position()? + *header.size;
+            if header.id == Cluster::ID {
+                let mut timestamp = None;
+                while reader.stream_position()? < end {
+                    let child = Header::read_from(reader)?;
+                    match child.id {
+                        Timestamp::ID => {
+                            timestamp = Some(Timestamp::read_element(&child, reader)?);
+                        }
+                        Position::ID => {
+                            let declared = *Position::read_element(&child, reader)?;
+                            let actual = position - self.segment_data_position;
+                            if declared != actual {
+                                if options.is_strict() {
+                                    return Err(crate::Error::PositionMismatch {
+                                        declared,
+                                        actual,
+                                    });
+                                }
+                                log::warn!(
+                                    "Cluster at {actual} declares Position {declared}, ignoring"
+                                );
+                            }
+                        }
+                        SimpleBlock::ID | BlockGroup::ID => break,
+                        _ => child.skip(reader)?,
+                    }
+                }
+                let timestamp = timestamp.ok_or(crate::Error::MissingElement(Timestamp::ID))?;
+                index.push((*timestamp as i64, position));
+            }
+            reader.seek(SeekFrom::Start(end))?;
+        }
+        Ok(index)
+    }
+
+    /// Decode the Clusters of this Segment in parallel from `data`, the whole file in memory.
+    ///
+    /// The Clusters are located with [`SegmentView::cluster_ranges`], and each one is decoded
+    /// independently, in the order they appear in the file.
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    pub fn clusters_par<'a>(
+        &self,
+        data: &'a [u8],
+    ) -> crate::Result<impl rayon::iter::IndexedParallelIterator<Item = crate::Result<Cluster>> + 'a>
+    {
+        use crate::Decode;
+        use rayon::prelude::*;
+
+        let ranges = self.cluster_ranges(data)?;
+        Ok(ranges
+            .into_par_iter()
+            .map(move |range| Cluster::decode(&mut &data[range])))
+    }
 }
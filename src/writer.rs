@@ -0,0 +1,393 @@
+//! Streaming writers for incremental muxing.
+
+use std::io::{Seek, SeekFrom, Write};
+
+use crate::base::VInt64;
+use crate::element::{Element, RESERVED_SIZE_WIDTH};
+use crate::io::blocking_impl::WriteTo;
+use crate::leaf::{Position, PrevSize, SeekId, SeekPosition, SimpleBlock, Timestamp};
+use crate::master::{Cluster, SeekHead};
+use crate::*;
+
+/// A `Write + Seek` sink, such as a file, able to back-patch the size of the elements written
+/// to it, as the streaming writers do.
+///
+/// An element is started with an unknown size on 8 bytes, so that an interrupted write still
+/// leaves a readable file, and its real size is patched in afterwards.
+#[derive(Debug)]
+pub struct SeekableWriter<W: Write + Seek> {
+    inner: W,
+}
+
+impl<W: Write + Seek> SeekableWriter<W> {
+    /// Wrap a writer, elements are written from its current position.
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Unwrap the writer, left where the last element ended.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Write the ID and a reserved unknown size of an element, and return the position of the
+    /// size, to pass to [`SeekableWriter::patch_size`] once the body is written.
+    pub fn start_element(&mut self, id: VInt64) -> crate::Result<u64> {
+        id.write_to(&mut self.inner)?;
+        let size_position = self.inner.stream_position()?;
+        let mut size = vec![];
+        VInt64::new_unknown().encode_with_width(RESERVED_SIZE_WIDTH, &mut size)?;
+        self.inner.write_all(&size)?;
+        Ok(size_position)
+    }
+
+    /// Patch the size reserved at `size_position` with the size of the body written since,
+    /// and return it. The writer is left at the end of the body.
+    pub fn patch_size(&mut self, size_position: u64) -> crate::Result<u64> {
+        let end = self.inner.stream_position()?;
+        let body_size = end - (size_position + RESERVED_SIZE_WIDTH as u64);
+        let mut size = vec![];
+        VInt64::new(body_size).encode_with_width(RESERVED_SIZE_WIDTH, &mut size)?;
+        self.inner.seek(SeekFrom::Start(size_position))?;
+        self.inner.write_all(&size)?;
+        self.inner.seek(SeekFrom::Start(end))?;
+        Ok(body_size)
+    }
+}
+
+impl<W: Write + Seek> Write for SeekableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write + Seek> Seek for SeekableWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Options for a [`ClusterWriter`], filling the Cluster elements used for error recovery.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClusterWriterOptions {
+    /// Position of the Segment data in the writer. When set, the Cluster `Position` is written,
+    /// as the offset of the Cluster from the start of the Segment data.
+    pub segment_data_position: Option<u64>,
+    /// Size of the previous Cluster, header included. When set, it is written as `PrevSize`.
+    pub prev_size: Option<u64>,
+}
+
+/// Streaming writer for a Cluster, the counterpart of the in-memory [`Cluster`].
+///
+/// Blocks are written to the sink as soon as they are pushed, so a Cluster can be muxed without
+/// holding all of its blocks in memory. The Cluster size is written as unknown upfront, and
+/// back-patched by [`ClusterWriter::finish`].
+pub struct ClusterWriter<W: Write + Seek> {
+    writer: SeekableWriter<W>,
+    options: ClusterWriterOptions,
+    start_position: u64,
+    size_position: u64,
+}
+
+impl<W: Write + Seek> ClusterWriter<W> {
+    /// Start a Cluster with the given timestamp at the current position of the writer.
+    pub fn new(writer: W, timestamp: u64) -> crate::Result<Self> {
+        Self::with_options(writer, timestamp, ClusterWriterOptions::default())
+    }
+
+    /// Start a Cluster with the given timestamp and options at the current position of the writer.
+    ///
+    /// Returns [`Error::InvalidSeekPosition`] if the writer is before the Segment data position
+    /// of the options, without writing anything.
+    pub fn with_options(
+        writer: W,
+        timestamp: u64,
+        options: ClusterWriterOptions,
+    ) -> crate::Result<Self> {
+        let mut writer = SeekableWriter::new(writer);
+        let start_position = writer.stream_position()?;
+        let position = options
+            .segment_data_position
+            .map(|segment_data_position| {
+                SeekPosition::from_absolute(start_position, segment_data_position)
+            })
+            .transpose()?;
+        let size_position = writer.start_element(Cluster::ID)?;
+        Timestamp(timestamp).write_to(&mut writer)?;
+        if let Some(position) = position {
+            Position(*position).write_to(&mut writer)?;
+        }
+        if let Some(prev_size) = options.prev_size {
+            PrevSize(prev_size).write_to(&mut writer)?;
+        }
+        Ok(Self {
+            writer,
+            options,
+            start_position,
+            size_position,
+        })
+    }
+
+    /// Write a SimpleBlock for the given track, with a timestamp relative to the Cluster timestamp.
+    ///
+    /// A single frame is written as is, multiple frames are laced with EBML lacing.
+    pub fn push_simple_block(
+        &mut self,
+        track: u64,
+        timestamp: i16,
+        keyframe: bool,
+        frames: &[&[u8]],
+    ) -> crate::Result<()> {
+        let mut body = vec![];
+        VInt64::new(track).encode(&mut body)?;
+        body.put_i16(timestamp);
+        let lacing = (frames.len() > 1).then_some(Lacer::Ebml);
+        body.put_u8(BlockFlags::new(keyframe, false, lacing, false).to_byte());
+        match lacing {
+            Some(lacer) => body.extend_from_slice(&lacer.lace(frames)),
+            None => frames
+                .iter()
+                .for_each(|frame| body.extend_from_slice(frame)),
+        }
+        SimpleBlock(body.into()).write_to(&mut self.writer)
+    }
+
+    /// Back-patch the Cluster size and return the writer, positioned at the end of the Cluster.
+    pub fn finish(mut self) -> crate::Result<W> {
+        self.writer.patch_size(self.size_position)?;
+        Ok(self.writer.into_inner())
+    }
+
+    /// Like [`ClusterWriter::finish`], also returning the options for the next Cluster,
+    /// with `prev_size` set to the size of this one.
+    pub fn finish_and_next(self) -> crate::Result<(W, ClusterWriterOptions)> {
+        let start_position = self.start_position;
+        let options = self.options;
+        let mut writer = self.finish()?;
+        let next = ClusterWriterOptions {
+            prev_size: Some(writer.stream_position()? - start_position),
+            ..options
+        };
+        Ok((writer, next))
+    }
+}
+
+/// Collects the positions of Top-Level Elements during a write pass, to build the SeekHead.
+///
+/// Positions are absolute in the writer, and stored relative to the Segment data as `SeekPosition`
+/// requires. The SeekHead is usually written last into space reserved with a Void element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeekHeadBuilder {
+    segment_data_position: u64,
+    entries: Vec<(VInt64, u64)>,
+}
+
+impl SeekHeadBuilder {
+    /// Start collecting positions for a Segment whose data starts at `segment_data_position`.
+    pub fn new(segment_data_position: u64) -> Self {
+        Self {
+            segment_data_position,
+            entries: vec![],
+        }
+    }
+
+    /// Record that the element with the given ID starts at the absolute `position`.
+    ///
+    /// Returns [`Error::InvalidSeekPosition`] if the position is before the Segment data.
+    pub fn record(&mut self, id: VInt64, position: u64) -> crate::Result<()> {
+        let relative = SeekPosition::from_absolute(position, self.segment_data_position)?;
+        self.entries.push((id, *relative));
+        Ok(())
+    }
+
+    /// Record that the element with the given ID starts at the current position of the writer,
+    /// to be called right before writing it.
+    pub fn record_current<S: Seek + ?Sized>(
+        &mut self,
+        id: VInt64,
+        writer: &mut S,
+    ) -> crate::Result<()> {
+        let position = writer.stream_position()?;
+        self.record(id, position)
+    }
+
+    /// Build the SeekHead, with one Seek per recorded element in recording order.
+    pub fn build(&self) -> crate::Result<SeekHead> {
+        let seek = self
+            .entries
+            .iter()
+            .map(|(id, position)| {
+                let mut seek_id = vec![];
+                id.encode(&mut seek_id)?;
+                Ok(crate::master::Seek {
+                    seek_id: SeekId(seek_id.into()),
+                    seek_position: SeekPosition(*position),
+                    ..Default::default()
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(SeekHead {
+            seek,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::Header;
+    use crate::io::blocking_impl::ReadFrom;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_seekable_writer() {
+        use crate::leaf::Title;
+        use crate::master::Info;
+
+        let mut buf = vec![0xAA; 3];
+        let mut cursor = Cursor::new(&mut buf);
+        cursor.set_position(3);
+        let mut writer = SeekableWriter::new(cursor);
+        let size_position = writer.start_element(Info::ID).unwrap();
+        assert_eq!(size_position, 3 + 4);
+        let title = Title("patched".to_string());
+        title.write_to(&mut writer).unwrap();
+        let mut body = vec![];
+        title.write_to(&mut body).unwrap();
+        assert_eq!(writer.patch_size(size_position).unwrap(), body.len() as u64);
+        assert_eq!(
+            writer.into_inner().position(),
+            3 + 4 + 8 + body.len() as u64
+        );
+
+        let header = Header::read_from(&mut &buf[3..]).unwrap();
+        assert_eq!(header.id, Info::ID);
+        assert_eq!(*header.size, body.len() as u64);
+        assert_eq!(header.size_width, Some(RESERVED_SIZE_WIDTH));
+    }
+
+    #[test]
+    fn test_cluster_writer() {
+        let mut writer = ClusterWriter::new(Cursor::new(vec![]), 1000).unwrap();
+        writer.push_simple_block(1, 0, true, &[&[1, 2, 3]]).unwrap();
+        writer
+            .push_simple_block(2, 20, false, &[&[4, 5], &[6]])
+            .unwrap();
+        let buf = writer.finish().unwrap().into_inner();
+
+        let header = Header::read_from(&mut &buf[..]).unwrap();
+        assert_eq!(header.size_width, Some(RESERVED_SIZE_WIDTH));
+
+        let cluster = Cluster::read_from(&mut &buf[..]).unwrap();
+        assert_eq!(*cluster.timestamp, 1000);
+        let frames: Vec<_> = cluster.frames().collect::<crate::Result<_>>().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].track_number, 1);
+        assert_eq!(*frames[0].timestamp, 1000);
+        assert!(frames[0].is_keyframe);
+        assert_eq!(frames[0].data, FrameData::Single(&[1, 2, 3]));
+        assert_eq!(frames[1].track_number, 2);
+        assert_eq!(*frames[1].timestamp, 1020);
+        assert!(!frames[1].is_keyframe);
+        assert_eq!(frames[1].data, FrameData::Multiple(vec![&[4, 5], &[6]]));
+    }
+
+    #[test]
+    fn test_cluster_writer_wide_track_number() {
+        let mut writer = ClusterWriter::new(Cursor::new(vec![]), 0).unwrap();
+        writer.push_simple_block(200, 5, true, &[&[7]]).unwrap();
+        let buf = writer.finish().unwrap().into_inner();
+
+        let cluster = Cluster::read_from(&mut &buf[..]).unwrap();
+        let ClusterBlock::Simple(block) = &cluster.blocks[0] else {
+            panic!("expected a SimpleBlock");
+        };
+        // 200 does not fit in a 1-byte VInt
+        assert_eq!(block[..2], [0x40, 200]);
+        let frames: Vec<_> = cluster.frames().collect::<crate::Result<_>>().unwrap();
+        assert_eq!(frames[0].track_number, 200);
+        assert_eq!(*frames[0].timestamp, 5);
+        assert_eq!(frames[0].data, FrameData::Single(&[7]));
+    }
+
+    #[test]
+    fn test_cluster_writer_recovery() {
+        use std::io::Seek;
+        let mut out = Cursor::new(vec![0; 5]);
+        out.seek(SeekFrom::End(0)).unwrap();
+        let options = ClusterWriterOptions {
+            segment_data_position: Some(5),
+            ..Default::default()
+        };
+        let mut writer = ClusterWriter::with_options(out, 0, options).unwrap();
+        writer.push_simple_block(1, 0, true, &[&[1, 2, 3]]).unwrap();
+        let (out, options) = writer.finish_and_next().unwrap();
+        let mut writer = ClusterWriter::with_options(out, 1000, options).unwrap();
+        writer.push_simple_block(1, 0, true, &[&[4, 5]]).unwrap();
+        let buf = writer.finish().unwrap().into_inner();
+
+        let mut reader = Cursor::new(&buf[5..]);
+        let first = Cluster::read_from(&mut reader).unwrap();
+        let first_size = reader.position();
+        assert_eq!(options.prev_size, Some(first_size));
+        let second_position = reader.position();
+        let second = Cluster::read_from(&mut reader).unwrap();
+
+        assert_eq!(first.position, Some(Position(0)));
+        assert_eq!(first.prev_size, None);
+        assert_eq!(second.position, Some(Position(second_position)));
+        assert_eq!(second.prev_size, Some(PrevSize(first_size)));
+    }
+
+    #[test]
+    fn test_cluster_writer_before_segment_data() {
+        let options = ClusterWriterOptions {
+            segment_data_position: Some(100),
+            ..Default::default()
+        };
+        let Err(err) = ClusterWriter::with_options(Cursor::new(vec![]), 0, options) else {
+            panic!("expected an error");
+        };
+        assert!(matches!(err, Error::InvalidSeekPosition(0)));
+    }
+
+    #[test]
+    fn test_seek_head_builder() {
+        use crate::master::{Cues, Info, Tracks};
+
+        let mut writer = Cursor::new(vec![0u8; 48]);
+        writer.seek(SeekFrom::End(0)).unwrap();
+        let mut builder = SeekHeadBuilder::new(48);
+        builder.record_current(Info::ID, &mut writer).unwrap();
+        Info::default().write_to(&mut writer).unwrap();
+        let tracks_position = writer.stream_position().unwrap();
+        builder.record(Tracks::ID, tracks_position).unwrap();
+        Tracks::default().write_to(&mut writer).unwrap();
+        builder.record(Cues::ID, 1000).unwrap();
+        assert!(matches!(
+            builder.record(Cues::ID, 10),
+            Err(Error::InvalidSeekPosition(10))
+        ));
+
+        let seek_head = builder.build().unwrap();
+        let targets = seek_head
+            .seek
+            .iter()
+            .map(|s| (s.seek_id.target_id().unwrap(), *s.seek_position))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            targets,
+            vec![
+                (Info::ID, 0),
+                (Tracks::ID, tracks_position - 48),
+                (Cues::ID, 952)
+            ]
+        );
+        assert_eq!(&seek_head.seek[0].seek_id[..], [0x15, 0x49, 0xA9, 0x66]);
+    }
+}
@@ -19,10 +19,31 @@ fn read_ebml() {
         doc_type_version: Some(DocTypeVersion(1)),
         doc_type_read_version: Some(DocTypeReadVersion(1)),
         void: None,
+        unknown: vec![],
+        size_widths: Default::default(),
     };
     assert_eq!(ebml, ebml_expected);
 }
 
+#[test]
+fn peek_doc_type_ebml() {
+    use mkv_element::io::blocking::*;
+    let ebml_hex = [
+        0x1a, 0x45, 0xDF, 0xA3, 0x93, 0x42, 0x82, 0x88, 0x6D, 0x61, 0x74, 0x72, 0x6F, 0x73, 0x6B,
+        0x61, 0x42, 0x87, 0x81, 0x01, 0x42, 0x85, 0x81, 0x01,
+    ];
+    let doc_type = peek_doc_type(&mut &ebml_hex[..]).unwrap();
+    assert_eq!(doc_type.as_deref(), Some("matroska"));
+
+    // EBML header without a DocType
+    let ebml_hex = [0x1a, 0x45, 0xDF, 0xA3, 0x84, 0x42, 0x87, 0x81, 0x01];
+    assert_eq!(peek_doc_type(&mut &ebml_hex[..]).unwrap(), None);
+
+    // not an EBML header
+    let segment_hex = [0x18, 0x53, 0x80, 0x67, 0x80];
+    assert!(peek_doc_type(&mut &segment_hex[..]).is_err());
+}
+
 #[test]
 fn write_ebml() {
     use mkv_element::io::blocking_impl::*;
@@ -36,6 +57,8 @@ fn write_ebml() {
         doc_type_version: Some(DocTypeVersion(1)),
         doc_type_read_version: Some(DocTypeReadVersion(1)),
         void: None,
+        unknown: vec![],
+        size_widths: Default::default(),
     };
     let mut ebml_buf = Vec::new();
     ebml.write_to(&mut ebml_buf).unwrap();
@@ -43,6 +66,73 @@ fn write_ebml() {
     assert_eq!(ebml, ebml_read);
 }
 
+#[test]
+fn default_ebml() {
+    use mkv_element::io::blocking_impl::*;
+    let ebml = Ebml::default();
+    assert_eq!(ebml.doc_type, Some(DocType("matroska".to_string())));
+    assert_eq!(*ebml.ebml_max_id_length, 4);
+    assert_eq!(*ebml.ebml_max_size_length, 8);
+    let mut ebml_buf = Vec::new();
+    ebml.write_to(&mut ebml_buf).unwrap();
+    let ebml_read = Ebml::read_from(&mut &ebml_buf[..]).unwrap();
+    assert_eq!(ebml, ebml_read);
+
+    let segment = Segment::default();
+    let mut segment_buf = Vec::new();
+    segment.write_to(&mut segment_buf).unwrap();
+    let segment_read = Segment::read_from(&mut &segment_buf[..]).unwrap();
+    assert_eq!(segment, segment_read);
+}
+
+#[test]
+fn default_info() {
+    let info = Info::default();
+    assert!(info.writing_app.starts_with("mkv-element-"));
+    assert!(info.muxing_app.starts_with("mkv-element-"));
+    assert_eq!(*info.timestamp_scale, 1_000_000);
+}
+
+#[test]
+fn ebml_crc32_strict() {
+    use mkv_element::ErrorCode;
+    use mkv_element::io::blocking_impl::*;
+    let mut ebml = Ebml {
+        crc32: None,
+        ebml_version: None,
+        ebml_read_version: None,
+        ebml_max_id_length: EbmlMaxIdLength(4),
+        ebml_max_size_length: EbmlMaxSizeLength(8),
+        doc_type: Some(DocType("matroska".to_string())),
+        doc_type_version: Some(DocTypeVersion(4)),
+        doc_type_read_version: Some(DocTypeReadVersion(2)),
+        void: None,
+        unknown: vec![],
+        size_widths: Default::default(),
+    };
+    let mut body = Vec::new();
+    ebml.encode_body(&mut body).unwrap();
+    ebml.crc32 = Some(Crc32::compute(&body));
+    let mut ebml_buf = Vec::new();
+    ebml.write_to(&mut ebml_buf).unwrap();
+
+    let strict = DecodeOptions::with_strictness(Strictness::Strict);
+    let lenient = DecodeOptions::with_strictness(Strictness::Lenient);
+    let ebml_read = Ebml::read_from_with(&mut &ebml_buf[..], &strict).unwrap();
+    assert_eq!(ebml, ebml_read);
+
+    // corrupt the DocType, which the CRC-32 protects
+    let pos = ebml_buf.windows(8).position(|w| w == b"matroska").unwrap();
+    ebml_buf[pos + 7] = b'b';
+
+    let err = Ebml::read_from_with(&mut &ebml_buf[..], &strict).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Crc32Mismatch);
+
+    let ebml_read = Ebml::read_from_with(&mut &ebml_buf[..], &lenient).unwrap();
+    assert_eq!(ebml_read.doc_type, Some(DocType("matroskb".to_string())));
+    assert_eq!(ebml_read.crc32, ebml.crc32);
+}
+
 #[cfg(feature = "tokio")]
 mod tokio_tests {
     use mkv_element::io::tokio_impl::*;
@@ -66,6 +156,8 @@ mod tokio_tests {
             doc_type_version: Some(DocTypeVersion(1)),
             doc_type_read_version: Some(DocTypeReadVersion(1)),
             void: None,
+            unknown: vec![],
+            size_widths: Default::default(),
         };
         assert_eq!(ebml, ebml_expected);
     }
@@ -82,6 +174,8 @@ mod tokio_tests {
             doc_type_version: Some(DocTypeVersion(1)),
             doc_type_read_version: Some(DocTypeReadVersion(1)),
             void: None,
+            unknown: vec![],
+            size_widths: Default::default(),
         };
         let mut ebml_buf = Vec::new();
         ebml.async_write_to(&mut ebml_buf).await.unwrap();
@@ -89,3 +183,466 @@ mod tokio_tests {
         assert_eq!(ebml, ebml_read);
     }
 }
+
+fn nested_segment() -> Segment {
+    let track = |n: u64| TrackEntry {
+        track_number: TrackNumber(n),
+        track_uid: TrackUid(n * 100),
+        track_type: TrackType(1),
+        codec_id: CodecId("V_VP9".to_string()),
+        video: Some(Video {
+            pixel_width: PixelWidth(1920),
+            pixel_height: PixelHeight(1080),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let cluster = |ts: u64| Cluster {
+        timestamp: Timestamp(ts),
+        blocks: vec![
+            SimpleBlock(vec![0x81, 0x00, 0x00, 0x80, 1, 2, 3].into()).into(),
+            BlockGroup {
+                block: Block(vec![0x81, 0x00, 0x28, 0x00, 4, 5].into()),
+                reference_block: vec![ReferenceBlock(-40)],
+                ..Default::default()
+            }
+            .into(),
+        ],
+        ..Default::default()
+    };
+    Segment {
+        info: Info {
+            title: Some(Title("nested".to_string())),
+            void: Some(Void { size: 127 }),
+            ..Default::default()
+        },
+        tracks: Some(Tracks {
+            crc32: Some(Crc32(0xDEAD_BEEF)),
+            track_entry: (1..=4).map(track).collect(),
+            ..Default::default()
+        }),
+        cluster: (0..50).map(|i| cluster(i * 1000)).collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn write_element_with_crc() {
+    use mkv_element::io::blocking_impl::*;
+    let mut info = Info {
+        title: Some(Title("with crc".to_string())),
+        ..Default::default()
+    };
+    let strict = DecodeOptions::with_strictness(Strictness::Strict);
+
+    let mut buf = Vec::new();
+    info.write_element_with_crc(&mut buf).unwrap();
+    let read = Info::read_from_with(&mut &buf[..], &strict).unwrap();
+    assert!(read.crc32.is_some());
+    info.crc32 = read.crc32;
+    assert_eq!(read, info);
+
+    // a stale CRC-32 is replaced, not written as is
+    info.crc32 = Some(Crc32(0xDEAD_BEEF));
+    let mut rewritten = Vec::new();
+    info.write_element_with_crc(&mut rewritten).unwrap();
+    assert_eq!(rewritten, buf);
+
+    // a CRC-32 whose size is 8 bytes wide, kept when preserving size widths, is replaced too
+    let crc = Header::new(Crc32::ID, VInt64::new(4)).with_size_width(8);
+    let mut body = Vec::new();
+    crc.write_to(&mut body).unwrap();
+    body.extend_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+    info.crc32 = None;
+    info.encode_body(&mut body).unwrap();
+    let mut wide = Vec::new();
+    Header::new(Info::ID, VInt64::new(body.len() as u64))
+        .write_to(&mut wide)
+        .unwrap();
+    wide.extend_from_slice(&body);
+    let options = DecodeOptions::default().preserving_size_width();
+    let read = Info::read_from_with(&mut &wide[..], &options).unwrap();
+    assert!(read.crc32.is_some());
+    let mut rewritten = Vec::new();
+    read.write_element_with_crc(&mut rewritten).unwrap();
+    let read = Info::read_from_with(&mut &rewritten[..], &strict).unwrap();
+    assert_eq!(read.title, info.title);
+    assert_eq!(rewritten.len(), wide.len());
+}
+
+#[test]
+fn encode_into_matches_encode() {
+    use mkv_element::io::blocking_impl::*;
+    let segment = nested_segment();
+    let mut expected = Vec::new();
+    segment.write_to(&mut expected).unwrap();
+    let mut actual = Vec::new();
+    segment.encode_into(&mut actual).unwrap();
+    assert_eq!(Segment::read_from(&mut &actual[..]).unwrap(), segment);
+    // only the sizes of the master elements are wider: the Segment, Info and Tracks,
+    // the TrackEntries and their Video, the Clusters and their BlockGroup
+    let masters = 3 + 4 * 2 + 50 * 2;
+    assert!(actual.len() <= expected.len() + 7 * masters);
+
+    // the size is reserved on 8 bytes and back-patched
+    let header = Header::read_from(&mut &actual[..]).unwrap();
+    assert_eq!(header.size_width(), Some(8));
+
+    // appends to existing content
+    let mut actual = vec![0xAA];
+    Ebml::default().encode_into(&mut actual).unwrap();
+    assert_eq!(actual[0], 0xAA);
+    assert_eq!(Ebml::read_from(&mut &actual[1..]).unwrap(), Ebml::default());
+}
+
+#[test]
+fn cluster_block_order_round_trip() {
+    use mkv_element::ClusterBlock;
+    use mkv_element::io::blocking_impl::*;
+    let simple = |n: u8| SimpleBlock(vec![0x81, 0x00, n, 0x80, n].into());
+    let cluster = Cluster {
+        timestamp: Timestamp(0),
+        blocks: vec![
+            simple(1).into(),
+            BlockGroup {
+                block: Block(vec![0x82, 0x00, 2, 0x00, 2].into()),
+                ..Default::default()
+            }
+            .into(),
+            simple(3).into(),
+        ],
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    cluster.write_to(&mut buf).unwrap();
+    let mut buf_into = Vec::new();
+    cluster.encode_into(&mut buf_into).unwrap();
+    assert_eq!(Cluster::read_from(&mut &buf_into[..]).unwrap(), cluster);
+
+    let read = Cluster::read_from(&mut &buf[..]).unwrap();
+    assert!(matches!(
+        read.blocks[..],
+        [
+            ClusterBlock::Simple(_),
+            ClusterBlock::Group(_),
+            ClusterBlock::Simple(_)
+        ]
+    ));
+    assert_eq!(read, cluster);
+}
+
+#[test]
+fn nested_error_context() {
+    use mkv_element::ErrorCode;
+    use mkv_element::io::blocking_impl::*;
+    let mut tracks = nested_segment().tracks.unwrap();
+    // a 9-byte DisplayWidth, too long for an unsigned integer
+    tracks.track_entry[0].video.as_mut().unwrap().unknown = vec![RawElement {
+        header: Header::new(DisplayWidth::ID, VInt64::new(9)),
+        body: vec![0; 9].into(),
+    }];
+    let mut buf = Vec::new();
+    tracks.write_to(&mut buf).unwrap();
+
+    let err = Tracks::read_from(&mut &buf[..]).unwrap_err();
+    assert_eq!(err.parents(), vec![Tracks::ID, TrackEntry::ID, Video::ID]);
+    assert_eq!(err.code(), ErrorCode::UnderDecode);
+    assert!(matches!(err.root(), mkv_element::Error::UnderDecode(id) if *id == DisplayWidth::ID));
+    assert!(
+        err.to_string()
+            .starts_with(&format!("in element {}", Tracks::ID))
+    );
+}
+
+#[test]
+fn preserve_unknown_round_trip() {
+    use mkv_element::io::blocking_impl::*;
+    let vendor = |id: u64, body: &'static [u8]| RawElement {
+        header: Header::new(VInt64::from_encoded(id), VInt64::new(body.len() as u64)),
+        body: body.into(),
+    };
+    let mut segment = nested_segment();
+    let mut wide = vendor(0x4D4D, b"vendor");
+    // a non-minimal size width must survive as well
+    wide.header = wide.header.with_size_width(4);
+    segment.unknown = vec![wide];
+    segment.cluster[0].unknown = vec![vendor(0x80, &[1, 2, 3])];
+    let mut buf = Vec::new();
+    segment.write_to(&mut buf).unwrap();
+
+    let options = DecodeOptions::default().preserving_unknown();
+    let read = Segment::read_from_with(&mut &buf[..], &options).unwrap();
+    assert_eq!(read, segment);
+    let mut rewritten = Vec::new();
+    read.write_to(&mut rewritten).unwrap();
+    assert_eq!(rewritten, buf);
+
+    // unknown elements are skipped by default
+    let read = Segment::read_from(&mut &buf[..]).unwrap();
+    assert!(read.unknown.is_empty());
+    assert!(read.cluster[0].unknown.is_empty());
+}
+
+#[test]
+fn on_unknown_hook() {
+    use mkv_element::io::blocking_impl::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static SKIPPED: AtomicUsize = AtomicUsize::new(0);
+
+    let junk = |id: u64| RawElement {
+        header: Header::new(VInt64::from_encoded(id), VInt64::new(2)),
+        body: vec![0xAB, 0xCD].into(),
+    };
+    let mut segment = nested_segment();
+    segment.unknown = vec![junk(0x4D4D)];
+    // junk at the beginning and the end of the Cluster, as in test7
+    segment.cluster[0].unknown = vec![junk(0x80), junk(0x81)];
+    let mut buf = Vec::new();
+    segment.write_to(&mut buf).unwrap();
+
+    let options = DecodeOptions {
+        on_unknown: Some(|header| {
+            assert_eq!(*header.size, 2);
+            SKIPPED.fetch_add(1, Ordering::Relaxed);
+        }),
+        ..Default::default()
+    };
+    let read = Segment::read_from_with(&mut &buf[..], &options).unwrap();
+    assert!(read.unknown.is_empty());
+    assert_eq!(SKIPPED.load(Ordering::Relaxed), 3);
+
+    // kept elements are not reported
+    Segment::read_from_with(&mut &buf[..], &options.clone().preserving_unknown()).unwrap();
+    assert_eq!(SKIPPED.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn decode_budget() {
+    use mkv_element::ErrorCode;
+    use mkv_element::io::blocking_impl::*;
+    let master = |id: VInt64, body: &[u8]| {
+        let mut buf = vec![];
+        Header::new(id, VInt64::new(body.len() as u64))
+            .write_to(&mut buf)
+            .unwrap();
+        buf.extend_from_slice(body);
+        buf
+    };
+    let void = [0xEC, 0x80];
+
+    // a Tags element made of 5000 empty Void elements
+    let buf = master(Tags::ID, &void.repeat(5000));
+    let tags = Tags::read_from(&mut &buf[..]).unwrap();
+    assert!(tags.tag.is_empty());
+
+    let options = DecodeOptions {
+        max_children: 1000,
+        ..Default::default()
+    };
+    let err = Tags::read_from_with(&mut &buf[..], &options).unwrap_err();
+    assert!(matches!(err, mkv_element::Error::DecodeBudgetExceeded(id) if id == Tags::ID));
+
+    // Clusters are decoded separately, with the same budget: Timestamp and 999 Voids fit
+    let body = [&[0xE7, 0x81, 0x00][..], &void.repeat(999)].concat();
+    Cluster::read_from_with(&mut &master(Cluster::ID, &body)[..], &options).unwrap();
+    let body = [&body[..], &void].concat();
+    let err = Cluster::read_from_with(&mut &master(Cluster::ID, &body)[..], &options).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DecodeBudgetExceeded);
+}
+
+#[test]
+fn decode_depth() {
+    use mkv_element::io::blocking_impl::*;
+    // ChapterAtoms nested 100 deep, in an EditionEntry in Chapters
+    let mut atom = ChapterAtom {
+        chapter_uid: ChapterUid(1),
+        ..Default::default()
+    };
+    for uid in 2..=100 {
+        atom = ChapterAtom {
+            chapter_uid: ChapterUid(uid),
+            chapter_atom: vec![atom],
+            ..Default::default()
+        };
+    }
+    let chapters = Chapters {
+        edition_entry: vec![EditionEntry {
+            chapter_atom: vec![atom],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let mut buf = vec![];
+    chapters.write_to(&mut buf).unwrap();
+
+    let err = Chapters::read_from(&mut &buf[..]).unwrap_err();
+    assert!(
+        matches!(err.root(), mkv_element::Error::MaxDepthExceeded(id) if *id == ChapterAtom::ID)
+    );
+    let options = DecodeOptions {
+        max_depth: 102,
+        ..Default::default()
+    };
+    assert_eq!(
+        Chapters::read_from_with(&mut &buf[..], &options).unwrap(),
+        chapters
+    );
+    let options = DecodeOptions {
+        max_depth: 101,
+        ..Default::default()
+    };
+    assert!(Chapters::read_from_with(&mut &buf[..], &options).is_err());
+
+    // far deeper than the stack allows, failing early instead of overflowing it
+    let header = |size: u64| {
+        let mut buf = vec![];
+        Header::new(ChapterAtom::ID, VInt64::new(size))
+            .write_to(&mut buf)
+            .unwrap();
+        buf
+    };
+    let mut sizes = vec![0];
+    for _ in 1..100_000 {
+        let inner = *sizes.last().unwrap();
+        sizes.push(inner + header(inner).len() as u64);
+    }
+    let buf: Vec<u8> = sizes.iter().rev().flat_map(|&size| header(size)).collect();
+    let err = ChapterAtom::read_from(&mut &buf[..]).unwrap_err();
+    assert!(matches!(
+        err.root(),
+        mkv_element::Error::MaxDepthExceeded(_)
+    ));
+}
+
+#[test]
+fn preserve_size_width() {
+    use mkv_element::io::blocking_impl::*;
+    let info = Info {
+        title: Some(Title("wide".to_string())),
+        ..Default::default()
+    };
+    let mut body = vec![];
+    info.encode_body(&mut body).unwrap();
+
+    // re-encode every child with an 8 bytes wide size
+    let mut wide = vec![];
+    let mut rest = &body[..];
+    while !rest.is_empty() {
+        let header = Header::read_from(&mut rest).unwrap().with_size_width(8);
+        header.write_to(&mut wide).unwrap();
+        let (child, tail) = rest.split_at(*header.size as usize);
+        wide.extend_from_slice(child);
+        rest = tail;
+    }
+    let mut buf = vec![];
+    Header::new(Info::ID, VInt64::new(wide.len() as u64))
+        .write_to(&mut buf)
+        .unwrap();
+    buf.extend_from_slice(&wide);
+
+    let options = DecodeOptions::default().preserving_size_width();
+    let read = Info::read_from_with(&mut &buf[..], &options).unwrap();
+    assert_eq!(read.size_widths.get(Title::ID, 0), Some(8));
+    let mut out = vec![];
+    read.write_to(&mut out).unwrap();
+    assert_eq!(out, buf);
+
+    // without the option, sizes are re-encoded minimally
+    let read = Info::read_from(&mut &buf[..]).unwrap();
+    assert!(read.size_widths.is_empty());
+    let (mut out, mut canonical) = (vec![], vec![]);
+    read.write_to(&mut out).unwrap();
+    info.write_to(&mut canonical).unwrap();
+    assert_eq!(out, canonical);
+}
+
+#[test]
+fn preserve_layout() {
+    use mkv_element::io::blocking_impl::*;
+    let child = |id: VInt64, body: &[u8], size_width| {
+        let mut buf = vec![];
+        Header::new(id, VInt64::new(body.len() as u64))
+            .with_size_width(size_width)
+            .write_to(&mut buf)
+            .unwrap();
+        buf.extend_from_slice(body);
+        buf
+    };
+    let info = Info {
+        title: Some(Title("layout".to_string())),
+        ..Default::default()
+    };
+    let cluster = Cluster {
+        timestamp: Timestamp(0),
+        ..Default::default()
+    };
+    let (mut info_body, mut cluster_body) = (vec![], vec![]);
+    info.encode_body(&mut info_body).unwrap();
+    cluster.encode_body(&mut cluster_body).unwrap();
+
+    // children out of the default order, an unknown one and two Voids, all with wide sizes
+    let body = [
+        child(Void::ID, &[0; 3], 4),
+        child(Cluster::ID, &cluster_body, 3),
+        child(VInt64::from_encoded(0x4D4D), b"vendor", 2),
+        child(Info::ID, &info_body, 8),
+        child(Void::ID, &[0; 1], 1),
+    ]
+    .concat();
+    let mut buf = vec![];
+    Header::new(Segment::ID, VInt64::new(body.len() as u64))
+        .write_to(&mut buf)
+        .unwrap();
+    buf.extend_from_slice(&body);
+
+    let options = DecodeOptions::default()
+        .preserving_unknown()
+        .preserving_size_width();
+    let read = Segment::read_from_with(&mut &buf[..], &options).unwrap();
+    assert_eq!(read.void, Some(Void { size: 4 }));
+    let mut out = vec![];
+    read.write_to(&mut out).unwrap();
+    assert_eq!(out, buf);
+    let mut streamed = std::io::Cursor::new(vec![]);
+    read.write_streaming(&mut streamed).unwrap();
+    assert!(streamed.into_inner().ends_with(&body));
+
+    // the CRC-32 goes first, followed by the children in their recorded layout
+    let mut with_crc = vec![];
+    read.write_element_with_crc(&mut with_crc).unwrap();
+    assert!(with_crc.ends_with(&body));
+    assert_eq!(
+        with_crc[with_crc.len() - body.len() - 6..][..2],
+        [0xBF, 0x84]
+    );
+
+    // the layout is not part of the value
+    let plain = Segment::read_from_with(
+        &mut &buf[..],
+        &DecodeOptions::default().preserving_unknown(),
+    )
+    .unwrap();
+    assert!(plain.size_widths.is_empty());
+    assert_eq!(read, plain);
+}
+
+#[test]
+#[ignore = "benchmark, run with --ignored --nocapture"]
+fn bench_nested_encode() {
+    use mkv_element::io::blocking_impl::*;
+    let segment = nested_segment();
+    let runs = 200;
+    let start = std::time::Instant::now();
+    for _ in 0..runs {
+        let mut buf = Vec::new();
+        segment.write_to(&mut buf).unwrap();
+    }
+    let encode = start.elapsed();
+    let start = std::time::Instant::now();
+    for _ in 0..runs {
+        let mut buf = Vec::new();
+        segment.encode_into(&mut buf).unwrap();
+    }
+    let encode_into = start.elapsed();
+    println!("write_to: {encode:?}, encode_into: {encode_into:?} ({runs} runs)");
+}
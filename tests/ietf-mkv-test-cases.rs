@@ -5,6 +5,14 @@ use mkv_element::ClusterBlock;
 use mkv_element::io::blocking_impl::*;
 use mkv_element::prelude::*;
 
+#[test]
+#[ignore = "this test requires the matroska-test-files submodule"]
+fn ietf_test_1_peek_doc_type() {
+    let mut file = std::fs::File::open("matroska-test-files/test_files/test1.mkv").unwrap();
+    let doc_type = peek_doc_type(&mut file).unwrap();
+    assert_eq!(doc_type.as_deref(), Some("matroska"));
+}
+
 // This file is the absolute minimum a compliant player should be able to handle.
 // The sample comes from the Big Buck Bunny open project.
 // It contains MPEG4.2 (DivX) video, (854x480) MP3 audio, uses only SimpleBlock (matroska DocType v2)
@@ -120,6 +128,15 @@ fn ietf_test_2() {
     assert!(*segment.info.timestamp_scale == 100_000);
 }
 
+#[test]
+#[ignore = "this test requires the matroska-test-files submodule"]
+fn ietf_test_2_strict_ebml_crc32() {
+    let mut file = std::fs::File::open("matroska-test-files/test_files/test2.mkv").unwrap();
+    let strict = DecodeOptions::with_strictness(Strictness::Strict);
+    let ebml_head = Ebml::read_from_with(&mut file, &strict).unwrap();
+    assert!(ebml_head.crc32.is_some(), "EBML header should have CRC-32");
+}
+
 // This file is using BlockGroup+Block only for audio and video frames.
 // It also removes 2 bytes off each video and audio frame since they are all equal.
 // These 2 bytes have to be put back in the frame before decoding. his file also contains CRC-32 values in the EBML header, the MetaSeek, the Segment Info, the Tracks and the Tags and PrevSize/Position in the Clusters for better error recovery.
@@ -180,6 +197,51 @@ fn ietf_test_3() {
     assert_eq!(audio_track.audio.as_ref().map(|a| *a.channels), Some(2));
 }
 
+#[test]
+#[ignore = "this test requires the matroska-test-files submodule"]
+fn ietf_test_3_header_stripping() {
+    use mkv_element::FrameOptions;
+
+    let mut file = std::fs::File::open("matroska-test-files/test_files/test3.mkv").unwrap();
+    let _ebml_head = Ebml::read_from(&mut file).unwrap();
+    let segment = Segment::read_from(&mut file).unwrap();
+    let tracks = segment.tracks.as_ref().unwrap();
+    let video_track = tracks
+        .track_entry
+        .iter()
+        .find(|t| *t.track_type == 1)
+        .unwrap();
+    let stripped = video_track
+        .content_encodings
+        .as_ref()
+        .unwrap()
+        .content_encoding[0]
+        .content_compression
+        .as_ref()
+        .unwrap()
+        .content_comp_settings
+        .clone()
+        .unwrap();
+
+    let options = FrameOptions {
+        decompress: true,
+        ..Default::default()
+    };
+    let cluster = segment.cluster.first().unwrap();
+    let mut restored = 0;
+    for frame in cluster.frames_with(tracks, options) {
+        let frame = frame.unwrap();
+        if frame.frame.track_number != *video_track.track_number {
+            continue;
+        }
+        for payload in &frame.payloads {
+            assert!(payload.starts_with(&stripped));
+            restored += 1;
+        }
+    }
+    assert!(restored > 0);
+}
+
 // This file is using the EBML feature that allows Master elements to have no known size.
 // It is used for live streams because they don't know ahead of time the size of the Segment (virtually infinite) and even sometimes the size of the Clusters (no caching on the server side).
 // The first timecode of the file also doesn't start at 0 since it's supposed to be a capture from something continuous.
@@ -274,8 +336,7 @@ fn ietf_test_4() {
                         }
                         _ => {
                             // unexpected element skip
-                            std::io::copy(&mut (&mut file).take(*header.size), &mut sink())
-                                .unwrap();
+                            header.skip(&mut file).unwrap();
                         }
                     }
                 }
@@ -290,6 +351,8 @@ fn ietf_test_4() {
     let segment = Segment {
         crc32: None,
         void: None,
+        unknown: vec![],
+        size_widths: Default::default(),
         seek_head: seekhead,
         info: info.unwrap(),
         cluster: clusters,
@@ -381,6 +444,25 @@ fn ietf_test_5() {
         assert!(subtitle_track.audio.is_none());
         assert!(subtitle_track.video.is_none());
     }
+
+    let mut codec_ids = segment.codec_ids();
+    codec_ids.sort();
+    assert_eq!(codec_ids, vec!["A_AAC", "S_TEXT/UTF8", "V_MPEG4/ISO/AVC"]);
+    assert_eq!(segment.tracks_with_codec("A_AAC").len(), 2);
+    assert_eq!(segment.tracks_with_codec("S_TEXT/UTF8").len(), 8);
+}
+
+#[cfg(feature = "json")]
+#[test]
+#[ignore = "this test requires the matroska-test-files submodule"]
+fn ietf_test_5_identify_json() {
+    let mut file = std::fs::File::open("matroska-test-files/test_files/test5.mkv").unwrap();
+    let view = mkv_element::view::MatroskaView::new(&mut file).unwrap();
+    let identify = view.identify_json();
+    let tracks = identify["tracks"].as_array().unwrap();
+    assert_eq!(tracks.len(), 11);
+    let video = tracks.iter().find(|t| t["type"] == "video").unwrap();
+    assert_eq!(video["codec"], "V_MPEG4/ISO/AVC");
 }
 
 // test6-tag.xml
@@ -423,6 +505,37 @@ fn ietf_test_6() {
     assert!(segment.cues.is_none(), "There should be no Cues element");
 }
 
+#[test]
+#[ignore = "this test requires the matroska-test-files submodule"]
+fn ietf_test_6_preserve_size_width() {
+    let mut file = std::fs::File::open("matroska-test-files/test_files/test6.mkv").unwrap();
+    let _ebml_head = Ebml::read_from(&mut file).unwrap();
+    let _segment = Header::read_from(&mut file).unwrap();
+    let options = DecodeOptions::default().preserving_size_width();
+    fn round_trip<T: Element>(body: &[u8], options: &DecodeOptions) {
+        let element = T::decode_body_with(&mut &body[..], options).unwrap();
+        let mut encoded = vec![];
+        element.encode_body(&mut encoded).unwrap();
+        assert_eq!(encoded, body, "{} is not re-encoded as is", T::ID);
+    }
+    // the sizes of the elements are randomly coded on 1 or 8 bytes
+    while let Ok(header) = Header::read_from(&mut file) {
+        let mut body = vec![0; *header.size as usize];
+        file.read_exact(&mut body).unwrap();
+        match header.id {
+            SeekHead::ID => round_trip::<SeekHead>(&body, &options),
+            Info::ID => round_trip::<Info>(&body, &options),
+            Tracks::ID => round_trip::<Tracks>(&body, &options),
+            Cues::ID => round_trip::<Cues>(&body, &options),
+            Cluster::ID => round_trip::<Cluster>(&body, &options),
+            Tags::ID => round_trip::<Tags>(&body, &options),
+            Chapters::ID => round_trip::<Chapters>(&body, &options),
+            Attachments::ID => round_trip::<Attachments>(&body, &options),
+            _ => {}
+        }
+    }
+}
+
 // Note:
 // This file contains junk elements (elements not defined in the specs) either at the beginning or the end of Clusters.
 // These elements should be skipped. There is also an invalid element at 451417 that should be skipped until the next valid Cluster is found.
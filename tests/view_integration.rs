@@ -17,6 +17,8 @@ fn ebml() -> Ebml {
         doc_type_version: Some(DocTypeVersion(4)),
         doc_type_read_version: Some(DocTypeReadVersion(2)),
         void: None,
+        unknown: vec![],
+        size_widths: Default::default(),
     }
 }
 
@@ -62,6 +64,8 @@ fn segment1() -> Segment {
     Segment {
         crc32: None,
         void: None,
+        unknown: vec![],
+        size_widths: Default::default(),
         seek_head: vec![],
         info,
         cluster: vec![cluster],
@@ -106,6 +110,8 @@ fn segment_without_clusters() -> Segment {
     Segment {
         crc32: None,
         void: None,
+        unknown: vec![],
+        size_widths: Default::default(),
         seek_head: vec![],
         info,
         cluster: vec![], // No clusters
@@ -138,6 +144,74 @@ fn test_basic_matroska_view() {
     assert_ne!(segment_view.first_cluster_position, 0);
 }
 
+#[test]
+fn test_header_only() {
+    let mut buffer = Vec::new();
+    ebml().write_to(&mut buffer).unwrap();
+
+    let result = MatroskaView::new(&mut Cursor::new(&buffer));
+    assert!(
+        matches!(result, Err(mkv_element::Error::MissingElement(id)) if id == Segment::ID),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_skip_unknown_size_unknown_element() {
+    let mut buffer = Vec::new();
+    ebml().write_to(&mut buffer).unwrap();
+    segment_without_clusters()
+        .write_unknown_size(&mut buffer)
+        .unwrap();
+    // unknown element of unknown size, holding a Void and another unknown-size unknown element
+    buffer.extend_from_slice(&[0x43, 0x21, 0xFF, 0xEC, 0x82, 0x00, 0x00]);
+    buffer.extend_from_slice(&[0x43, 0x22, 0xFF, 0x81, 0x81, 0x00]);
+    let cluster_position = buffer.len() as u64;
+    Cluster {
+        timestamp: Timestamp(0),
+        ..Default::default()
+    }
+    .write_to(&mut buffer)
+    .unwrap();
+
+    let view = MatroskaView::new(&mut Cursor::new(&buffer)).unwrap();
+    let segment_view = &view.segments[0];
+    assert!(segment_view.tracks.is_some());
+    assert_eq!(segment_view.first_cluster_position, cluster_position);
+}
+
+#[test]
+fn test_view_on_unknown() {
+    use mkv_element::io::blocking_impl::ReadFrom;
+    use mkv_element::view::SegmentView;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static SKIPPED: AtomicUsize = AtomicUsize::new(0);
+
+    let junk = |id: u64| RawElement {
+        header: Header::new(VInt64::from_encoded(id), VInt64::new(2)),
+        body: vec![0xAB, 0xCD].into(),
+    };
+    let mut segment = segment_without_clusters();
+    segment.unknown = vec![junk(0x4D4D)];
+    segment.info.unknown = vec![junk(0x4D4E)];
+    let mut buffer = Vec::new();
+    ebml().write_to(&mut buffer).unwrap();
+    segment.write_to(&mut buffer).unwrap();
+
+    let mut cursor = Cursor::new(&buffer);
+    Ebml::read_from(&mut cursor).unwrap();
+    let options = DecodeOptions {
+        on_unknown: Some(|_| {
+            SKIPPED.fetch_add(1, Ordering::Relaxed);
+        }),
+        ..Default::default()
+    };
+    let segments = SegmentView::new_with(&mut cursor, &options).unwrap();
+    assert_eq!(segments.len(), 1);
+    // one at the Segment level, one in the Info
+    assert_eq!(SKIPPED.load(Ordering::Relaxed), 2);
+}
+
 #[test]
 fn test_segment_without_clusters() {
     // Create a Matroska file with EBML header and a segment without clusters
@@ -207,6 +281,7 @@ fn test_unsize_segment() {
     let segment_header = Header {
         id: Segment::ID,
         size: VInt64::new_unknown(),
+        size_width: None,
     };
     let segment = segment1();
     let mut buffer = Vec::new();
@@ -222,6 +297,61 @@ fn test_unsize_segment() {
     assert_ne!(segment_view.first_cluster_position, 0);
 }
 
+#[test]
+fn test_extract_attachment() {
+    let ebml_header = ebml();
+    let font = (0..=255u8).cycle().take(10_000).collect::<Vec<_>>();
+    let cover = vec![0xFF, 0xD8, 0xFF, 0xE0];
+    let mut segment = segment1();
+    segment.attachments = Some(Attachments {
+        attached_file: vec![
+            AttachedFile {
+                file_name: FileName("cover.jpg".to_string()),
+                file_media_type: FileMediaType("image/jpeg".to_string()),
+                file_data: FileData(cover.clone().into()),
+                file_uid: FileUid(1),
+                ..Default::default()
+            },
+            AttachedFile {
+                file_name: FileName("font.ttf".to_string()),
+                file_media_type: FileMediaType("font/ttf".to_string()),
+                file_data: FileData(font.clone().into()),
+                file_uid: FileUid(2),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    });
+
+    let mut buffer = Vec::new();
+    ebml_header.write_to(&mut buffer).unwrap();
+    segment.write_to(&mut buffer).unwrap();
+
+    let mut cursor = Cursor::new(&buffer);
+    let view = MatroskaView::new(&mut cursor).unwrap();
+    assert!(view.segments[0].attachments_position.is_some());
+    // only the metadata of the attached files is read, their data stays in the file
+    let attachments = &view.segments[0].attachments;
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[1].file.file_name.0, "font.ttf");
+    assert!(attachments[1].file.file_data.is_empty());
+    assert_eq!(attachments[1].data_size, font.len() as u64);
+
+    let mut out = Vec::new();
+    let n = view.extract_attachment(&mut cursor, 2, &mut out).unwrap();
+    assert_eq!(n, font.len() as u64);
+    assert_eq!(out, font);
+
+    let mut out = Vec::new();
+    view.extract_attachment(&mut cursor, 1, &mut out).unwrap();
+    assert_eq!(out, cover);
+
+    assert!(matches!(
+        view.extract_attachment(&mut cursor, 3, &mut Vec::new()),
+        Err(mkv_element::Error::AttachmentNotFound(3))
+    ));
+}
+
 #[cfg(feature = "tokio")]
 mod async_tests {
     use super::*;
@@ -248,6 +378,18 @@ mod async_tests {
         assert_ne!(segment_view.first_cluster_position, 0);
     }
 
+    #[tokio::test]
+    async fn test_header_only_async() {
+        let mut buffer = Vec::new();
+        ebml().async_write_to(&mut buffer).await.unwrap();
+
+        let result = MatroskaView::new_async(&mut Cursor::new(&buffer)).await;
+        assert!(
+            matches!(result, Err(mkv_element::Error::MissingElement(id)) if id == Segment::ID),
+            "{result:?}"
+        );
+    }
+
     #[tokio::test]
     async fn test_segment_without_clusters_async() {
         // Create a Matroska file with EBML header and a segment without clusters
@@ -317,6 +459,7 @@ mod async_tests {
         let segment_header = Header {
             id: Segment::ID,
             size: VInt64::new_unknown(),
+            size_width: None,
         };
         let segment = segment1();
         let mut buffer = Vec::new();
@@ -335,3 +478,171 @@ mod async_tests {
         assert_ne!(segment_view.first_cluster_position, 0);
     }
 }
+
+#[test]
+fn test_metadata_digest_ignores_padding() {
+    let plain = segment1();
+    let mut padded = segment1();
+    padded.info.void = Some(Void { size: 16 });
+    let tracks = padded.tracks.as_mut().unwrap();
+    tracks.crc32 = Some(Crc32(0x1234_5678));
+    tracks.track_entry[0].void = Some(Void { size: 3 });
+    let mut retitled = segment1();
+    retitled.info.title = Some(Title("Another Title".to_string()));
+
+    let digest = |segment: &Segment| {
+        let mut buffer = Vec::new();
+        ebml().write_to(&mut buffer).unwrap();
+        segment.write_to(&mut buffer).unwrap();
+        let view = MatroskaView::new(&mut Cursor::new(&buffer)).unwrap();
+        view.segments[0].metadata_digest().unwrap()
+    };
+    assert_eq!(digest(&plain), digest(&padded));
+    assert_ne!(digest(&plain), digest(&retitled));
+}
+
+#[test]
+fn test_build_index_without_cues() {
+    use mkv_element::io::blocking_impl::ReadFrom;
+    use std::io::{Seek, SeekFrom};
+
+    let mut segment = segment1();
+    segment.cluster = (0..3)
+        .map(|i| Cluster {
+            timestamp: Timestamp(i * 2000),
+            blocks: vec![SimpleBlock(vec![0x81, 0x00, 0x00, 0x80, i as u8].into()).into()],
+            ..Default::default()
+        })
+        .collect();
+    let mut buffer = Vec::new();
+    ebml().write_to(&mut buffer).unwrap();
+    segment.write_to(&mut buffer).unwrap();
+    segment_without_clusters().write_to(&mut buffer).unwrap();
+
+    let mut cursor = Cursor::new(&buffer);
+    let view = MatroskaView::new(&mut cursor).unwrap();
+    assert!(view.segments[0].cues.is_none());
+    let index = view.segments[0].build_index(&mut cursor).unwrap();
+    assert_eq!(
+        index.iter().map(|(ts, _)| *ts).collect::<Vec<_>>(),
+        vec![0, 2000, 4000]
+    );
+    for (ts, position) in index {
+        cursor.seek(SeekFrom::Start(position)).unwrap();
+        let cluster = Cluster::read_from(&mut cursor).unwrap();
+        assert_eq!(*cluster.timestamp as i64, ts);
+    }
+    assert!(
+        view.segments[1]
+            .build_index(&mut cursor)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_build_index_position_mismatch() {
+    let mut segment = segment1();
+    segment.cluster = vec![Cluster {
+        timestamp: Timestamp(0),
+        position: Some(Position(12345)),
+        blocks: vec![SimpleBlock(vec![0x81, 0x00, 0x00, 0x80, 0x00].into()).into()],
+        ..Default::default()
+    }];
+    let mut buffer = Vec::new();
+    ebml().write_to(&mut buffer).unwrap();
+    segment.write_to(&mut buffer).unwrap();
+
+    let mut cursor = Cursor::new(&buffer);
+    let view = MatroskaView::new(&mut cursor).unwrap();
+    let segment_view = &view.segments[0];
+    // only logged by default
+    let index = segment_view.build_index(&mut cursor).unwrap();
+    assert_eq!(index.len(), 1);
+
+    let strict = DecodeOptions::with_strictness(Strictness::Strict);
+    let err = segment_view
+        .build_index_with(&mut cursor, &strict)
+        .unwrap_err();
+    let actual = segment_view.first_cluster_position - segment_view.segment_data_position;
+    assert!(matches!(
+        err,
+        mkv_element::Error::PositionMismatch { declared: 12345, actual: a } if a == actual
+    ));
+
+    // a correct Position passes
+    let mut segment = segment1();
+    segment.cluster[0].position = Some(Position(actual));
+    let mut buffer = Vec::new();
+    ebml().write_to(&mut buffer).unwrap();
+    segment.write_to(&mut buffer).unwrap();
+    let mut cursor = Cursor::new(&buffer);
+    let view = MatroskaView::new(&mut cursor).unwrap();
+    view.segments[0]
+        .build_index_with(&mut cursor, &strict)
+        .unwrap();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_clusters_par() {
+    use rayon::prelude::*;
+
+    let mut segment = segment1();
+    segment.cluster = (0..8)
+        .map(|i| Cluster {
+            timestamp: Timestamp(i * 1000),
+            blocks: vec![SimpleBlock(vec![0x81, 0x00, 0x00, 0x80, i as u8].into()).into()],
+            ..Default::default()
+        })
+        .collect();
+    let mut buffer = Vec::new();
+    ebml().write_to(&mut buffer).unwrap();
+    segment.write_to(&mut buffer).unwrap();
+    segment_without_clusters().write_to(&mut buffer).unwrap();
+
+    let view = MatroskaView::new(&mut Cursor::new(&buffer)).unwrap();
+    assert_eq!(view.segments[0].cluster_ranges(&buffer).unwrap().len(), 8);
+    assert!(view.segments[1].cluster_ranges(&buffer).unwrap().is_empty());
+
+    let clusters: Vec<Cluster> = view.segments[0]
+        .clusters_par(&buffer)
+        .unwrap()
+        .collect::<mkv_element::Result<_>>()
+        .unwrap();
+    assert_eq!(clusters, segment.cluster);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_identify_json() {
+    let mut buffer = Vec::new();
+    ebml().write_to(&mut buffer).unwrap();
+    segment1().write_to(&mut buffer).unwrap();
+
+    let view = MatroskaView::new(&mut Cursor::new(&buffer)).unwrap();
+    let identify = view.identify_json();
+    assert_eq!(identify["container"]["type"], "Matroska");
+    assert_eq!(
+        identify["container"]["properties"]["duration"],
+        30_000_000_000u64
+    );
+    let tracks = identify["tracks"].as_array().unwrap();
+    assert_eq!(tracks.len(), 1);
+    assert_eq!(tracks[0]["type"], "video");
+    assert_eq!(tracks[0]["codec"], "V_VP9");
+    assert_eq!(tracks[0]["properties"]["pixel_dimensions"], "1920x1080");
+    assert_eq!(tracks[0]["properties"]["language"], "eng");
+    assert!(identify["attachments"].as_array().unwrap().is_empty());
+
+    // a view without Segment is described as empty
+    let view = MatroskaView {
+        segments: vec![],
+        ..view
+    };
+    let identify = view.identify_json();
+    assert_eq!(identify["container"]["type"], "Matroska");
+    assert!(identify["tracks"].as_array().unwrap().is_empty());
+    assert!(identify["attachments"].as_array().unwrap().is_empty());
+    assert!(identify["chapters"].as_array().unwrap().is_empty());
+}